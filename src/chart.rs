@@ -0,0 +1,239 @@
+use chrono::{
+    DateTime, Datelike, Days, Duration, Local, NaiveDate, NaiveTime, TimeZone, Timelike, Utc,
+};
+use enum_iterator::{all, Sequence};
+
+use crate::{
+    models::{Color, Counter},
+    value::{total, ValueKind},
+};
+
+/// How far back the dashboard looks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Sequence)]
+pub enum TimeRange {
+    Day,
+    #[default]
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+/// Width of one bar in the activity chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Interval {
+    Hour,
+    Day,
+    Week,
+}
+
+/// A value at the start of a bucket. `value` is raw, in the series' kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Point {
+    pub at: DateTime<Utc>,
+    pub value: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Series {
+    pub kind: ValueKind,
+    pub points: Vec<Point>,
+}
+
+/// Net change per color over a time range, in `kind`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakdown {
+    pub kind: ValueKind,
+    pub slices: Vec<(Color, i64)>,
+}
+
+impl TimeRange {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Day => "Last 24 hours",
+            Self::Week => "Last 7 days",
+            Self::Month => "Last 30 days",
+            Self::Quarter => "Last 12 weeks",
+            Self::Year => "Last year",
+        }
+    }
+
+    pub const fn interval(self) -> Interval {
+        match self {
+            Self::Day => Interval::Hour,
+            Self::Week | Self::Month => Interval::Day,
+            Self::Quarter | Self::Year => Interval::Week,
+        }
+    }
+
+    const fn len(self) -> u32 {
+        match self {
+            Self::Day => 24,
+            Self::Week => 7,
+            Self::Month => 30,
+            Self::Quarter => 12,
+            Self::Year => 52,
+        }
+    }
+
+    /// Start of every bucket in the range, oldest first. The last bucket
+    /// holds `now`.
+    pub fn buckets(self, now: DateTime<Local>) -> Vec<DateTime<Utc>> {
+        let interval = self.interval();
+        let mut starts = Vec::new();
+        let mut start = interval.start_of(now);
+        for _ in 0..self.len() {
+            starts.push(start.with_timezone(&Utc));
+            match interval.previous(start) {
+                Some(previous) => start = previous,
+                None => break,
+            }
+        }
+        starts.reverse();
+
+        starts
+    }
+}
+
+impl Interval {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Hour => "per hour",
+            Self::Day => "per day",
+            Self::Week => "per week",
+        }
+    }
+
+    /// `strftime` format for a bucket's start on an axis.
+    pub const fn format(self) -> &'static str {
+        match self {
+            Self::Hour => "%H:%M",
+            Self::Day | Self::Week => "%b %-d",
+        }
+    }
+
+    fn start_of(self, at: DateTime<Local>) -> DateTime<Local> {
+        let date = at.date_naive();
+        let start = match self {
+            Self::Hour => at
+                .with_minute(0)
+                .and_then(|at| at.with_second(0))
+                .and_then(|at| at.with_nanosecond(0)),
+            Self::Day => midnight(date),
+            Self::Week => date
+                .checked_sub_days(Days::new(u64::from(date.weekday().num_days_from_monday())))
+                .and_then(midnight),
+        };
+
+        start.unwrap_or(at)
+    }
+
+    fn previous(self, start: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self {
+            Self::Hour => Some(start - Duration::hours(1)),
+            Self::Day => midnight(start.date_naive().checked_sub_days(Days::new(1))?),
+            Self::Week => midnight(start.date_naive().checked_sub_days(Days::new(7))?),
+        }
+    }
+}
+
+/// Local midnight. Days starting inside a DST gap begin at the first valid time.
+fn midnight(date: NaiveDate) -> Option<DateTime<Local>> {
+    let at = date.and_time(NaiveTime::MIN);
+    Local.from_local_datetime(&at).earliest().or_else(|| {
+        Local
+            .from_local_datetime(&(at + Duration::hours(1)))
+            .earliest()
+    })
+}
+
+/// The kind every value of `counters` can be expressed in without losing digits.
+fn common_kind(counters: &[Counter]) -> ValueKind {
+    total(counters.iter().map(|counter| (counter.kind, 0))).0
+}
+
+/// Index of the bucket holding `at`, if it falls inside the range.
+fn bucket_of(buckets: &[DateTime<Utc>], at: DateTime<Utc>) -> Option<usize> {
+    if buckets.first().is_none_or(|first| at < *first) {
+        return None;
+    }
+    Some(buckets.partition_point(|start| *start <= at) - 1)
+}
+
+/// Net change of all `counters` in each bucket.
+pub fn activity(counters: &[Counter], buckets: &[DateTime<Utc>]) -> Series {
+    let kind = common_kind(counters);
+    let mut values = vec![0_i64; buckets.len()];
+    for counter in counters {
+        for event in &counter.events {
+            if let Some(index) = bucket_of(buckets, event.at) {
+                values[index] =
+                    values[index].saturating_add(counter.kind.rescale(event.delta, kind));
+            }
+        }
+    }
+
+    Series {
+        kind,
+        points: buckets
+            .iter()
+            .zip(values)
+            .map(|(&at, value)| Point { at, value })
+            .collect(),
+    }
+}
+
+/// Sum of the values of all `counters` at the end of each bucket. Their logs
+/// need only cover the buckets.
+pub fn running_total(counters: &[Counter], buckets: &[DateTime<Utc>]) -> Series {
+    let kind = common_kind(counters);
+    let points = buckets
+        .iter()
+        .enumerate()
+        .map(|(index, &at)| {
+            let end = buckets.get(index + 1).copied();
+            let value = counters.iter().fold(0_i64, |sum, counter| {
+                let value = end.map_or(counter.count, |end| value_before(counter, end));
+                sum.saturating_add(counter.kind.rescale(value, kind))
+            });
+            Point { at, value }
+        })
+        .collect();
+
+    Series { kind, points }
+}
+
+/// The counter's value just before `at`, read from its event log.
+fn value_before(counter: &Counter, at: DateTime<Utc>) -> i64 {
+    let index = counter.events.partition_point(|event| event.at < at);
+    match (index.checked_sub(1), counter.events.first()) {
+        (Some(last), _) => counter.events[last].value,
+        (None, Some(first)) => first.value.saturating_sub(first.delta),
+        (None, None) => counter.count,
+    }
+}
+
+/// Net change of `counters` since `since`, grouped by color. Counters with a
+/// custom color count towards the nearest named one.
+pub fn by_color(counters: &[Counter], since: DateTime<Utc>) -> Breakdown {
+    let kind = common_kind(counters);
+    let slices = all::<Color>()
+        .filter(|color| counters.iter().any(|counter| counter.color == *color))
+        .map(|color| {
+            let change = counters
+                .iter()
+                .filter(|counter| counter.color == color)
+                .fold(0_i64, |sum, counter| {
+                    let delta = counter
+                        .events
+                        .iter()
+                        .filter(|event| event.at >= since)
+                        .fold(0_i64, |sum, event| sum.saturating_add(event.delta));
+                    sum.saturating_add(counter.kind.rescale(delta, kind))
+                });
+            (color, change)
+        })
+        .collect();
+
+    Breakdown { kind, slices }
+}
@@ -0,0 +1,392 @@
+use std::fmt;
+
+use enum_iterator::all;
+use serde::{Deserialize, Serialize};
+
+use crate::models::Color;
+
+/// Lightness of the derived shades, matching the Tailwind steps the named
+/// colors use: 200/300 in light mode and 500/400 in dark mode.
+const LIGHT: f64 = 0.87;
+const LIGHT_HOVER: f64 = 0.78;
+const DARK: f64 = 0.5;
+const DARK_HOVER: f64 = 0.6;
+
+/// Colors less saturated than this are treated as grey.
+const GREY_SATURATION: f64 = 0.15;
+
+/// A user-defined sRGB color, saved as `#rrggbb`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseColorError {
+    Empty,
+    Invalid,
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "enter a color"),
+            Self::Invalid => write!(f, "expected #rrggbb or hsl(h, s%, l%)"),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// Background and text colors for light and dark mode, derived from one color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shades {
+    pub light: Rgb,
+    pub light_hover: Rgb,
+    pub dark: Rgb,
+    pub dark_hover: Rgb,
+}
+
+impl Rgb {
+    pub const BLACK: Self = Self::new(0, 0, 0);
+    pub const WHITE: Self = Self::new(255, 255, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Parses `#rgb`, `#rrggbb` (the `#` is optional) or `hsl(h, s%, l%)`.
+    pub fn parse(text: &str) -> Result<Self, ParseColorError> {
+        let text = text.trim().to_lowercase();
+        if text.is_empty() {
+            return Err(ParseColorError::Empty);
+        }
+
+        if let Some(args) = text
+            .strip_prefix("hsl(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            return Self::parse_hsl(args);
+        }
+
+        let hex = text.strip_prefix('#').unwrap_or(&text);
+        let digits = hex
+            .chars()
+            .map(|c| c.to_digit(16).and_then(|digit| u8::try_from(digit).ok()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ParseColorError::Invalid)?;
+
+        match digits[..] {
+            [r, g, b] => Ok(Self::new(r * 17, g * 17, b * 17)),
+            [r1, r2, g1, g2, b1, b2] => Ok(Self::new(r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2)),
+            _ => Err(ParseColorError::Invalid),
+        }
+    }
+
+    /// `h, s%, l%`, also accepting spaces instead of commas.
+    fn parse_hsl(args: &str) -> Result<Self, ParseColorError> {
+        let parts = args
+            .split([',', ' '])
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>();
+        let [hue, saturation, lightness] = parts[..] else {
+            return Err(ParseColorError::Invalid);
+        };
+
+        let number = |part: &str, suffix: &str| {
+            part.strip_suffix(suffix)
+                .unwrap_or(part)
+                .parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .ok_or(ParseColorError::Invalid)
+        };
+        let percent = |part: &str| {
+            number(part, "%")
+                .map(|value| value / 100.0)
+                .and_then(|value| {
+                    (0.0..=1.0)
+                        .contains(&value)
+                        .then_some(value)
+                        .ok_or(ParseColorError::Invalid)
+                })
+        };
+
+        Ok(Self::from_hsl(
+            number(hue, "deg")?,
+            percent(saturation)?,
+            percent(lightness)?,
+        ))
+    }
+
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// `hue` in degrees, `saturation` and `lightness` from 0 to 1.
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Self {
+        let chroma = (1.0 - 2.0f64.mul_add(lightness, -1.0).abs()) * saturation;
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let second = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector {
+            s if s < 1.0 => (chroma, second, 0.0),
+            s if s < 2.0 => (second, chroma, 0.0),
+            s if s < 3.0 => (0.0, chroma, second),
+            s if s < 4.0 => (0.0, second, chroma),
+            s if s < 5.0 => (second, 0.0, chroma),
+            _ => (chroma, 0.0, second),
+        };
+        let offset = lightness - chroma / 2.0;
+
+        Self::new(
+            channel(r + offset),
+            channel(g + offset),
+            channel(b + offset),
+        )
+    }
+
+    /// Hue in degrees, saturation and lightness from 0 to 1.
+    pub fn to_hsl(self) -> (f64, f64, f64) {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| f64::from(c) / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let lightness = f64::midpoint(max, min);
+        if self.r == self.g && self.g == self.b {
+            return (0.0, 0.0, lightness);
+        }
+
+        let delta = max - min;
+        let saturation = delta / (1.0 - 2.0f64.mul_add(lightness, -1.0).abs());
+        let hue = if self.r >= self.g && self.r >= self.b {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if self.g >= self.b {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+
+        (hue, saturation, lightness)
+    }
+
+    pub fn with_lightness(self, lightness: f64) -> Self {
+        let (hue, saturation, _) = self.to_hsl();
+        Self::from_hsl(hue, saturation, lightness)
+    }
+
+    /// Relative luminance as defined by WCAG.
+    fn luminance(self) -> f64 {
+        let linear = |c: u8| {
+            let c = f64::from(c) / 255.0;
+            if c <= 0.040_45 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.0722f64.mul_add(
+            linear(self.b),
+            0.2126f64.mul_add(linear(self.r), 0.7152 * linear(self.g)),
+        )
+    }
+
+    /// WCAG contrast ratio, from 1 (none) to 21 (black on white).
+    pub fn contrast_ratio(self, other: Self) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// Black or white, whichever is easier to read on this color.
+    pub fn text_color(self) -> Self {
+        if self.contrast_ratio(Self::BLACK) >= self.contrast_ratio(Self::WHITE) {
+            Self::BLACK
+        } else {
+            Self::WHITE
+        }
+    }
+
+    pub fn shades(self) -> Shades {
+        Shades {
+            light: self.with_lightness(LIGHT),
+            light_hover: self.with_lightness(LIGHT_HOVER),
+            dark: self.with_lightness(DARK),
+            dark_hover: self.with_lightness(DARK_HOVER),
+        }
+    }
+
+    /// The named color closest in hue, so custom colors still take part in
+    /// filtering and sorting by color. Greys map to [`Color::System`].
+    pub fn nearest_preset(self) -> Color {
+        let (hue, saturation, _) = self.to_hsl();
+        if saturation < GREY_SATURATION {
+            return Color::System;
+        }
+
+        let distance = |other: f64| {
+            let difference = (hue - other).abs();
+            difference.min(360.0 - difference)
+        };
+        all::<Color>()
+            .filter_map(|color| color.rgb().map(|rgb| (color, rgb.to_hsl().0)))
+            .min_by(|(_, a), (_, b)| distance(*a).total_cmp(&distance(*b)))
+            .map_or(Color::System, |(color, _)| color)
+    }
+}
+
+/// Converts a channel from 0–1 to 0–255, clamping out-of-range values.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn channel(value: f64) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+impl TryFrom<String> for Rgb {
+    type Error = ParseColorError;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Self::parse(&text)
+    }
+}
+
+impl From<Rgb> for String {
+    fn from(rgb: Rgb) -> Self {
+        rgb.to_hex()
+    }
+}
+
+impl Shades {
+    /// CSS custom properties read by the `counter-color` class in `main.css`.
+    pub fn style(&self) -> String {
+        let icon = |background: Rgb| {
+            if background.text_color() == Rgb::WHITE {
+                "invert(1)"
+            } else {
+                "none"
+            }
+        };
+
+        format!(
+            "--counter-bg: {}; --counter-bg-hover: {}; --counter-fg: {}; --counter-icon: {}; \
+             --counter-bg-dark: {}; --counter-bg-dark-hover: {}; --counter-fg-dark: {}; --counter-icon-dark: {};",
+            self.light,
+            self.light_hover,
+            self.light.text_color(),
+            icon(self.light),
+            self.dark,
+            self.dark_hover,
+            self.dark.text_color(),
+            icon(self.dark),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Channels are whole numbers, so the hue of a converted color drifts a little.
+    fn assert_hsl(rgb: Rgb, (hue, saturation, lightness): (f64, f64, f64)) {
+        let (h, s, l) = rgb.to_hsl();
+        assert!((h - hue).abs() < 1.0, "{rgb}: hue {h}, expected {hue}");
+        assert!(
+            (s - saturation).abs() < 0.01,
+            "{rgb}: saturation {s}, expected {saturation}"
+        );
+        assert!(
+            (l - lightness).abs() < 0.01,
+            "{rgb}: lightness {l}, expected {lightness}"
+        );
+    }
+
+    #[test]
+    fn parses_hex_and_hsl() {
+        assert_eq!(Rgb::parse("#336699"), Ok(Rgb::new(0x33, 0x66, 0x99)));
+        assert_eq!(Rgb::parse(" 369 "), Ok(Rgb::new(0x33, 0x66, 0x99)));
+        assert_eq!(
+            Rgb::parse("HSL(210, 50%, 40%)"),
+            Ok(Rgb::new(0x33, 0x66, 0x99))
+        );
+        assert_eq!(
+            Rgb::parse("hsl(210deg 50% 40%)"),
+            Ok(Rgb::new(0x33, 0x66, 0x99))
+        );
+        assert_eq!(Rgb::parse(""), Err(ParseColorError::Empty));
+        assert_eq!(Rgb::parse("#12345"), Err(ParseColorError::Invalid));
+        assert_eq!(Rgb::parse("#gggggg"), Err(ParseColorError::Invalid));
+        assert_eq!(
+            Rgb::parse("hsl(0, 120%, 50%)"),
+            Err(ParseColorError::Invalid)
+        );
+    }
+
+    #[test]
+    fn converts_known_colors_to_and_from_hsl() {
+        let known = [
+            (Rgb::new(255, 0, 0), (0.0, 1.0, 0.5)),
+            (Rgb::new(0, 255, 0), (120.0, 1.0, 0.5)),
+            (Rgb::new(0, 0, 255), (240.0, 1.0, 0.5)),
+            (Rgb::new(0x33, 0x66, 0x99), (210.0, 0.5, 0.4)),
+            (Rgb::new(255, 0, 255), (300.0, 1.0, 0.5)),
+            (Rgb::WHITE, (0.0, 0.0, 1.0)),
+            (Rgb::BLACK, (0.0, 0.0, 0.0)),
+        ];
+
+        for (rgb, hsl) in known {
+            assert_hsl(rgb, hsl);
+            assert_eq!(Rgb::from_hsl(hsl.0, hsl.1, hsl.2), rgb);
+        }
+        assert_eq!(Rgb::from_hsl(-150.0, 0.5, 0.4), Rgb::new(0x33, 0x66, 0x99));
+    }
+
+    #[test]
+    fn round_trips_through_hex_and_hsl() {
+        let rgb = Rgb::new(0x12, 0xab, 0xef);
+        assert_eq!(rgb.to_hex(), "#12abef");
+        assert_eq!(Rgb::parse(&rgb.to_hex()), Ok(rgb));
+
+        let (hue, saturation, lightness) = rgb.to_hsl();
+        assert_eq!(Rgb::from_hsl(hue, saturation, lightness), rgb);
+    }
+
+    #[test]
+    fn picks_readable_text_on_light_and_dark_swatches() {
+        for light in [
+            Rgb::WHITE,
+            Rgb::new(0xfd, 0xe0, 0x47),
+            Rgb::new(0xbf, 0xdb, 0xfe),
+        ] {
+            assert_eq!(light.text_color(), Rgb::BLACK, "{light}");
+        }
+        for dark in [
+            Rgb::BLACK,
+            Rgb::new(0x1e, 0x3a, 0x8a),
+            Rgb::new(0x7f, 0x1d, 0x1d),
+        ] {
+            assert_eq!(dark.text_color(), Rgb::WHITE, "{dark}");
+        }
+        assert!((Rgb::BLACK.contrast_ratio(Rgb::WHITE) - 21.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn shades_keep_their_lightness_and_text_stays_readable() {
+        let shades = Rgb::new(0x33, 0x66, 0x99).shades();
+        assert_hsl(shades.light, (210.0, 0.5, LIGHT));
+        assert_hsl(shades.dark, (210.0, 0.5, DARK));
+        assert_eq!(shades.light.text_color(), Rgb::BLACK);
+        assert!(shades.dark.contrast_ratio(shades.dark.text_color()) >= 4.5);
+    }
+
+    #[test]
+    fn custom_colors_map_to_the_nearest_preset() {
+        assert_eq!(Rgb::new(0xee, 0x11, 0x22).nearest_preset(), Color::Red);
+        assert_eq!(Rgb::new(0x11, 0x22, 0xee).nearest_preset(), Color::Blue);
+        assert_eq!(Rgb::new(0x80, 0x80, 0x88).nearest_preset(), Color::System);
+    }
+}
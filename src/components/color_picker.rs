@@ -0,0 +1,62 @@
+use dioxus::prelude::*;
+
+use crate::color::{ParseColorError, Rgb};
+
+/// Picks a custom color, either with the platform color dialog or by typing
+/// a hex or HSL value. Like `ValueInput`, typed text is kept while focused
+/// and a rejected value is explained inline.
+#[component]
+pub fn ColorPicker(value: Rgb, onchange: EventHandler<Rgb>) -> Element {
+    let mut draft = use_signal(|| None::<String>);
+    let mut error = use_signal(|| None::<ParseColorError>);
+
+    let text = draft().unwrap_or_else(|| value.to_hex());
+
+    rsx! {
+        div { class: "flex flex-row items-center gap-1",
+            input {
+                class: "h-8 w-10 cursor-pointer rounded bg-transparent",
+                r#type: "color",
+                aria_label: "Pick a color",
+                value: value.to_hex(),
+                oninput: move |e| {
+                    if let Ok(rgb) = Rgb::parse(&e.value()) {
+                        onchange(rgb);
+                    }
+                },
+            }
+            div { class: "relative grow",
+                input {
+                    class: format!(
+                        "input input-bordered input-sm w-full {}",
+                        if error().is_some() { "input-error" } else { "" },
+                    ),
+                    r#type: "text",
+                    aria_label: "Color as hex or HSL",
+                    aria_invalid: error().is_some(),
+                    value: text,
+                    oninput: move |e| {
+                        let text = e.value();
+                        match Rgb::parse(&text) {
+                            Ok(rgb) => {
+                                error.set(None);
+                                onchange(rgb);
+                            }
+                            Err(err) => error.set(Some(err)),
+                        }
+                        draft.set(Some(text));
+                    },
+                    onblur: move |_| {
+                        draft.set(None);
+                        error.set(None);
+                    },
+                }
+                if let Some(err) = error() {
+                    span { class: "absolute left-0 top-full z-10 text-xs text-error whitespace-nowrap",
+                        "{err}"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -2,7 +2,20 @@ use dioxus::prelude::*;
 use enum_iterator::all;
 use uuid::Uuid;
 
-use crate::models::{Color, Counter};
+use crate::{
+    color::Rgb,
+    components::{
+        color_picker::ColorPicker, event_log::EventLog, modal::Modal,
+        schedule_editor::ScheduleEditor, value_input::ValueInput,
+    },
+    filter::normalize,
+    fuzzy::segments,
+    icon::{BundledIcon, Icon},
+    markdown,
+    models::{Color, Counter, Group, Limits},
+    schedule::ResetSchedule,
+    value::{ValueKind, MAX_DECIMAL_PLACES},
+};
 
 #[component]
 pub fn TallyCounter(
@@ -15,24 +28,78 @@ pub fn TallyCounter(
     onmovedown: EventHandler<Uuid>,
     onmovebottom: EventHandler<Uuid>,
     onsetcolor: EventHandler<(Uuid, Color)>,
+    oncustomcolorchange: EventHandler<(Uuid, Rgb)>,
     ontitlechange: EventHandler<(Uuid, String)>,
-    onvaluechange: EventHandler<(Uuid, usize)>,
+    onvaluechange: EventHandler<(Uuid, i64)>,
+    onstepchange: EventHandler<(Uuid, i64)>,
+    onlimitschange: EventHandler<(Uuid, Limits)>,
+    onkindchange: EventHandler<(Uuid, ValueKind)>,
+    onresetvaluechange: EventHandler<(Uuid, i64)>,
+    onreset: EventHandler<Uuid>,
+    onschedulechange: EventHandler<(Uuid, Option<ResetSchedule>)>,
+    ongroupchange: EventHandler<(Uuid, Option<Uuid>)>,
+    ontagschange: EventHandler<(Uuid, Vec<String>)>,
+    onnoteschange: EventHandler<(Uuid, String)>,
+    oniconchange: EventHandler<(Uuid, Option<Icon>)>,
+    /// Opens the counter's detail page.
+    onopen: EventHandler<Uuid>,
+    /// Groups the counter can be moved to.
+    #[props(default)]
+    groups: Vec<Group>,
+    #[props(into, default = rsx!())] drag_handle: Element,
+    #[props(default)] highlight: Vec<usize>,
+    /// Whether the move buttons are shown. Off while the list is sorted.
+    #[props(default = true)]
+    reorderable: bool,
 ) -> Element {
     let mut is_open = use_signal(|| false);
 
-    let get_color_name = |color: Color| match color {
-        crate::models::Color::System => "base",
-        crate::models::Color::Red => "red",
-        crate::models::Color::Orange => "orange",
-        crate::models::Color::Yellow => "yellow",
-        crate::models::Color::Green => "green",
-        crate::models::Color::Blue => "blue",
-        crate::models::Color::Purple => "purple",
+    // The log queries the store, so it is only rendered while it is shown.
+    let mut history_open = use_signal(|| false);
+
+    let mut new_tag = use_signal(String::new);
+
+    let mut notes_open = use_signal(|| false);
+    let mut editing_notes = use_signal(|| false);
+
+    let is_color_selected = |color: Color| counter.custom_color.is_none() && counter.color == color;
+
+    let limits = counter.limits;
+
+    let tags = counter.tags.clone();
+
+    let notes = counter.notes.clone();
+
+    let icon = counter.icon.clone();
+
+    let kind = counter.kind;
+
+    // A group removed since the counter joined it counts as no group.
+    let in_group = groups.iter().any(|group| counter.group == Some(group.id));
+
+    let (lower_bound, upper_bound) = (counter.lower_bound(), counter.upper_bound());
+
+    let goal_style = if counter.goal_reached() {
+        "ring-2 ring-success"
+    } else {
+        ""
     };
 
-    let is_color_selected = |color: Color| counter.color == color;
+    // Custom colors are drawn from CSS variables; named colors use their Tailwind classes.
+    let custom_shades = counter.custom_color.map(Rgb::shades);
+
+    let color_style = custom_shades
+        .map(|shades| shades.style())
+        .unwrap_or_default();
+
+    let (text_color, icon_color) = if custom_shades.is_some() {
+        ("counter-color-text", "counter-color-icon")
+    } else {
+        ("dark:text-white", "dark:invert")
+    };
 
     let background_color = match counter.color {
+        _ if custom_shades.is_some() => "counter-color",
         Color::System => "bg-base-200 hover:bg-base-300",
         Color::Red => "bg-red-200 hover:bg-red-300 dark:bg-red-500 dark:hover:bg-red-400",
         Color::Orange => {
@@ -50,39 +117,77 @@ pub fn TallyCounter(
 
     rsx! {
         div { class: "container",
-            div { class: "join-item p-2 rounded-lg flex justify-center text-base-content {background_color} shadow-md max-w-[36rem] mx-14 hover:ease-in-out",
+            Modal {
+                id: "history-{counter.id}",
+                onclose: move |()| history_open.set(false),
+                if history_open() {
+                    EventLog { counter: counter.clone() }
+                }
+            }
+            div {
+                class: "join-item p-2 rounded-lg flex flex-col justify-center text-base-content {background_color} {goal_style} shadow-md max-w-[36rem] mx-14 hover:ease-in-out",
+                style: color_style,
                 div { class: "join flex flex-row",
-                    input {
-                        class: "join-item w-full bg-inherit dark:text-white",
-                        value: counter.title,
-                        oninput: move |e| ontitlechange((counter.id, e.value())),
-                        r#type: "text"
+                    {drag_handle}
+                    if let Some(Icon::Bundled(bundled)) = icon {
+                        img {
+                            class: "h-6 w-6 self-center mx-1 {icon_color}",
+                            alt: bundled.label(),
+                            src: bundled.asset(),
+                        }
+                    }
+                    if let Some(Icon::Emoji(emoji)) = icon.clone() {
+                        span { class: "self-center mx-1 text-xl leading-none", "{emoji}" }
+                    }
+                    div { class: "join-item relative w-full",
+                        // Highlights sit behind the transparent title input, laid out with the same text.
+                        if !highlight.is_empty() {
+                            div {
+                                aria_hidden: "true",
+                                class: "absolute inset-0 pointer-events-none overflow-hidden whitespace-pre text-transparent",
+                                for (segment , matched) in segments(&counter.title, &highlight) {
+                                    if matched {
+                                        mark { class: "rounded-sm bg-yellow-300/70 text-transparent", "{segment}" }
+                                    } else {
+                                        span { "{segment}" }
+                                    }
+                                }
+                            }
+                        }
+                        input {
+                            class: format!("relative w-full bg-transparent {text_color}"),
+                            value: counter.title,
+                            oninput: move |e| ontitlechange((counter.id, e.value())),
+                            r#type: "text"
+                        }
                     }
-                    input {
-                        class: "join-item w-full text-end bg-inherit dark:text-white",
-                        oninput: move |e| {
-                            if let Ok(value) = e.value().trim().parse::<usize>() {
+                    ValueInput {
+                        class: "join-item w-full",
+                        input_class: format!("w-full text-end bg-inherit {text_color}"),
+                        kind,
+                        value: counter.count,
+                        onchange: move |value: Option<i64>| {
+                            if let Some(value) = value {
                                 onvaluechange((counter.id, value));
                             }
                         },
-                        value: counter.count.to_string()
                     }
                     div { class: "space-x-1 flex flex-row mx-4",
                         button {
                             class: "btn btn-circle {background_color} border-0 hover:ease-in-out",
-                            disabled: counter.count == usize::MIN,
+                            disabled: counter.count <= lower_bound,
                             onclick: move |_| ondecrement(counter.id),
                             img {
-                                class: "h-6 w-6 dark:invert",
+                                class: "h-6 w-6 {icon_color}",
                                 src: asset!("public/assets/minus.svg")
                             }
                         }
                         button {
                             class: "btn btn-circle {background_color} border-0 hover:ease-in-out",
-                            disabled: counter.count == usize::MAX,
+                            disabled: counter.count >= upper_bound,
                             onclick: move |_| onincrement(counter.id),
                             img {
-                                class: "h-6 w-6 dark:invert",
+                                class: "h-6 w-6 {icon_color}",
                                 src: asset!("public/assets/plus.svg")
                             }
                         }
@@ -92,7 +197,7 @@ pub fn TallyCounter(
                             "open": is_open(),
                             summary { class: "btn m-1 border-0 {background_color} hover:ease-in-out",
                                 img {
-                                    class: "h-1/2 w-1/2 dark:invert",
+                                    class: "h-1/2 w-1/2 {icon_color}",
                                     src: asset!("public/assets/more-horizontal.svg")
                                 }
                             }
@@ -110,8 +215,7 @@ pub fn TallyCounter(
                                                     if matches!(color, Color::System) {
                                                         String::new()
                                                     } else {
-                                                        let color = get_color_name(color);
-                                                        format!("bg-{color}-500")
+                                                        format!("bg-{}-500", color.palette_name())
                                                     },
                                                 ),
                                                 onclick: move |_| onsetcolor((counter.id, color))
@@ -120,45 +224,376 @@ pub fn TallyCounter(
                                     }
                                 }
 
-                                div { class: "space-x-1",
-                                    div { class: "!join",
+                                label {
+                                    class: "form-control mt-2",
+                                    onclick: move |e| e.stop_propagation(),
+                                    div { class: "label",
+                                        span { class: "label-text", "Custom color" }
+                                    }
+                                    ColorPicker {
+                                        value: counter.custom_color.or_else(|| counter.color.rgb()).unwrap_or(Rgb::new(0x80, 0x80, 0x80)),
+                                        onchange: move |rgb| oncustomcolorchange((counter.id, rgb)),
+                                    }
+                                }
+
+                                div {
+                                    class: "form-control mt-2",
+                                    onclick: move |e| e.stop_propagation(),
+                                    div { class: "label",
+                                        span { class: "label-text", "Icon" }
+                                    }
+                                    div { class: "flex flex-wrap gap-0.5",
                                         button {
-                                            class: "block btn join-item tooltip",
-                                            "data-tip": "Move Up",
-                                            onclick: move |_| { onmoveup(counter.id) },
+                                            class: format!("btn btn-square btn-sm {}", if icon.is_none() { "btn-active" } else { "" }),
+                                            title: "No icon",
+                                            onclick: move |_| oniconchange((counter.id, None)),
+                                            "–"
+                                        }
+                                        for bundled in all::<BundledIcon>() {
+                                            button {
+                                                class: format!(
+                                                    "btn btn-square btn-sm {}",
+                                                    if icon == Some(Icon::Bundled(bundled)) { "btn-active" } else { "" },
+                                                ),
+                                                title: bundled.label(),
+                                                onclick: move |_| oniconchange((counter.id, Some(Icon::Bundled(bundled)))),
+                                                img {
+                                                    class: "h-5 w-5 dark:invert",
+                                                    alt: bundled.label(),
+                                                    src: bundled.asset(),
+                                                }
+                                            }
+                                        }
+                                    }
+                                    input {
+                                        class: "input input-bordered input-sm w-full mt-1",
+                                        r#type: "text",
+                                        placeholder: "Or type an emoji",
+                                        value: if let Some(Icon::Emoji(emoji)) = &icon { emoji.clone() } else { String::new() },
+                                        oninput: move |e| {
+                                            if let Some(icon) = Icon::emoji(&e.value()) {
+                                                oniconchange((counter.id, Some(icon)));
+                                            }
+                                        },
+                                    }
+                                }
+
+                                div {
+                                    class: "grid grid-cols-2 gap-1 my-2",
+                                    onclick: move |e| e.stop_propagation(),
+                                    if !groups.is_empty() {
+                                        label { class: "form-control col-span-2",
+                                            div { class: "label",
+                                                span { class: "label-text", "Group" }
+                                            }
+                                            select {
+                                                class: "select select-bordered select-sm",
+                                                onchange: move |e| {
+                                                    ongroupchange((counter.id, e.value().parse::<Uuid>().ok()));
+                                                },
+                                                option {
+                                                    value: "",
+                                                    selected: !in_group,
+                                                    "No group"
+                                                }
+                                                for group in groups {
+                                                    option {
+                                                        value: "{group.id}",
+                                                        selected: counter.group == Some(group.id),
+                                                        "{group.name}"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                    label { class: "form-control",
+                                        div { class: "label",
+                                            span { class: "label-text", "Type" }
+                                        }
+                                        select {
+                                            class: "select select-bordered select-sm",
+                                            onchange: move |e| {
+                                                let kind = match e.value().as_str() {
+                                                    "signed" => ValueKind::Signed,
+                                                    "decimal" => ValueKind::Decimal { places: 2 },
+                                                    _ => ValueKind::Unsigned,
+                                                };
+                                                onkindchange((counter.id, kind));
+                                            },
+                                            option {
+                                                value: "unsigned",
+                                                selected: matches!(kind, ValueKind::Unsigned),
+                                                "Whole, not negative"
+                                            }
+                                            option {
+                                                value: "signed",
+                                                selected: matches!(kind, ValueKind::Signed),
+                                                "Whole"
+                                            }
+                                            option {
+                                                value: "decimal",
+                                                selected: matches!(kind, ValueKind::Decimal { .. }),
+                                                "Decimal"
+                                            }
+                                        }
+                                    }
+                                    if let ValueKind::Decimal { places } = kind {
+                                        label { class: "form-control",
+                                            div { class: "label",
+                                                span { class: "label-text", "Decimal places" }
+                                            }
+                                            input {
+                                                class: "input input-bordered input-sm w-20",
+                                                r#type: "number",
+                                                min: 1,
+                                                max: MAX_DECIMAL_PLACES,
+                                                value: places.to_string(),
+                                                // Fewer places truncate every value, so only a
+                                                // finished entry is applied, not each keystroke.
+                                                onchange: move |e| {
+                                                    if let Ok(places) = e.value().trim().parse::<u8>() {
+                                                        if (1..=MAX_DECIMAL_PLACES).contains(&places) {
+                                                            onkindchange((counter.id, ValueKind::Decimal { places }));
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+
+                                label {
+                                    class: "form-control my-2",
+                                    onclick: move |e| e.stop_propagation(),
+                                    div { class: "label",
+                                        span { class: "label-text", "Step" }
+                                    }
+                                    ValueInput {
+                                        input_class: "input input-bordered input-sm w-full",
+                                        kind,
+                                        value: counter.step,
+                                        onchange: move |step: Option<i64>| {
+                                            if let Some(step) = step.filter(|step| *step > 0) {
+                                                onstepchange((counter.id, step));
+                                            }
+                                        },
+                                    }
+                                }
+
+                                label {
+                                    class: "form-control mb-2",
+                                    onclick: move |e| e.stop_propagation(),
+                                    div { class: "label",
+                                        span { class: "label-text", "Reset to" }
+                                    }
+                                    ValueInput {
+                                        input_class: "input input-bordered input-sm w-full",
+                                        kind,
+                                        value: counter.reset_value,
+                                        onchange: move |reset_value: Option<i64>| {
+                                            if let Some(reset_value) = reset_value {
+                                                onresetvaluechange((counter.id, reset_value));
+                                            }
+                                        },
+                                    }
+                                }
+
+                                label {
+                                    class: "form-control mb-2",
+                                    onclick: move |e| e.stop_propagation(),
+                                    div { class: "label",
+                                        span { class: "label-text", "Reset automatically" }
+                                    }
+                                    ScheduleEditor {
+                                        schedule: counter.schedule,
+                                        onchange: move |schedule| onschedulechange((counter.id, schedule)),
+                                    }
+                                }
+
+                                div {
+                                    class: "form-control mb-2",
+                                    onclick: move |e| e.stop_propagation(),
+                                    div { class: "label",
+                                        span { class: "label-text", "Tags" }
+                                    }
+                                    if !tags.is_empty() {
+                                        div { class: "flex flex-wrap gap-1 mb-1",
+                                            for tag in tags.clone() {
+                                                span { class: "badge badge-outline gap-1",
+                                                    "{tag}"
+                                                    button {
+                                                        class: "opacity-60 hover:opacity-100",
+                                                        aria_label: "Remove tag {tag}",
+                                                        onclick: {
+                                                            let tags = tags.clone();
+                                                            move |_| {
+                                                                let remaining = tags.iter().filter(|own| **own != tag).cloned().collect();
+                                                                ontagschange((counter.id, remaining));
+                                                            }
+                                                        },
+                                                        "✕"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                    input {
+                                        class: "input input-bordered input-sm w-full",
+                                        r#type: "text",
+                                        placeholder: "Add a tag and press Enter",
+                                        value: new_tag(),
+                                        oninput: move |e| new_tag.set(e.value()),
+                                        onkeydown: move |e: KeyboardEvent| {
+                                            if e.key() != Key::Enter {
+                                                return;
+                                            }
+                                            let tag = new_tag().trim().to_string();
+                                            let exists = tags.iter().any(|own| normalize(own) == normalize(&tag));
+                                            if !tag.is_empty() && !exists {
+                                                let tags = tags.iter().cloned().chain(std::iter::once(tag)).collect();
+                                                ontagschange((counter.id, tags));
+                                            }
+                                            new_tag.set(String::new());
+                                        },
+                                    }
+                                }
+
+                                div {
+                                    class: "grid grid-cols-3 gap-1 mb-2",
+                                    onclick: move |e| e.stop_propagation(),
+                                    label { class: "form-control",
+                                        div { class: "label",
+                                            span { class: "label-text", "Min" }
+                                        }
+                                        ValueInput {
+                                            input_class: "input input-bordered input-sm w-20",
+                                            kind,
+                                            value: limits.min,
+                                            optional: true,
+                                            onchange: move |min| onlimitschange((counter.id, Limits { min, ..limits })),
+                                        }
+                                    }
+                                    label { class: "form-control",
+                                        div { class: "label",
+                                            span { class: "label-text", "Max" }
+                                        }
+                                        ValueInput {
+                                            input_class: "input input-bordered input-sm w-20",
+                                            kind,
+                                            value: limits.max,
+                                            optional: true,
+                                            onchange: move |max| onlimitschange((counter.id, Limits { max, ..limits })),
+                                        }
+                                    }
+                                    label { class: "form-control",
+                                        div { class: "label",
+                                            span { class: "label-text", "Goal" }
+                                        }
+                                        ValueInput {
+                                            input_class: "input input-bordered input-sm w-20",
+                                            kind,
+                                            value: limits.goal,
+                                            optional: true,
+                                            onchange: move |goal| onlimitschange((counter.id, Limits { goal, ..limits })),
+                                        }
+                                    }
+                                }
+
+                                div { class: "space-x-1",
+                                    if reorderable {
+                                        div { class: "!join",
+                                            button {
+                                                class: "block btn join-item tooltip",
+                                                "data-tip": "Move Up",
+                                                onclick: move |_| { onmoveup(counter.id) },
+                                                img {
+                                                    class: "h-6 w-6 dark:invert",
+                                                    src: asset!("public/assets/chevron-up.svg")
+                                                }
+                                            }
+                                            button {
+                                                class: "block btn content-center join-item tooltip",
+                                                "data-tip": "Move Down",
+                                                onclick: move |_| { onmovedown(counter.id) },
+                                                img {
+                                                    class: "h-6 w-6 dark:invert",
+                                                    src: asset!("public/assets/chevron-down.svg")
+                                                }
+                                            }
+                                        }
+
+                                        div { class: "!join",
+                                            button {
+                                                class: "block btn join-item tooltip",
+                                                "data-tip": "Move Top",
+                                                onclick: move |_| { onmovetop(counter.id) },
+                                                img {
+                                                    class: "h-6 w-6 dark:invert",
+                                                    src: asset!("public/assets/chevron-top.svg")
+                                                }
+                                            }
+                                            button {
+                                                class: "block btn content-center join-item tooltip",
+                                                "data-tip": "Move Bottom",
+                                                onclick: move |_| { onmovebottom(counter.id) },
+                                                img {
+                                                    class: "h-6 w-6 dark:invert",
+                                                    src: asset!("public/assets/chevron-bottom.svg")
+                                                }
+                                            }
+                                        }
+                                    }
+
+                                    button {
+                                        class: "btn tooltip",
+                                        "data-tip": "Reset",
+                                        onclick: move |_| { onreset(counter.id) },
+                                        div { class: "flex justify-center items-center",
                                             img {
-                                                class: "h-6 w-6 dark:invert",
-                                                src: asset!("public/assets/chevron-up.svg")
+                                                class: "h-6 w-6 dark:invert object-contain",
+                                                src: asset!("public/assets/reset.svg")
                                             }
                                         }
-                                        button {
-                                            class: "block btn content-center join-item tooltip",
-                                            "data-tip": "Move Down",
-                                            onclick: move |_| { onmovedown(counter.id) },
+                                    }
+
+                                    button {
+                                        class: "btn tooltip",
+                                        "data-tip": "Details",
+                                        onclick: move |_| onopen(counter.id),
+                                        div { class: "flex justify-center items-center",
                                             img {
-                                                class: "h-6 w-6 dark:invert",
-                                                src: asset!("public/assets/chevron-down.svg")
+                                                class: "h-6 w-6 dark:invert object-contain",
+                                                src: asset!("public/assets/info.svg")
                                             }
                                         }
                                     }
 
-                                    div { class: "!join",
-                                        button {
-                                            class: "block btn join-item tooltip",
-                                            "data-tip": "Move Top",
-                                            onclick: move |_| { onmovetop(counter.id) },
+                                    button {
+                                        class: "btn tooltip",
+                                        "data-tip": "Notes",
+                                        onclick: move |_| {
+                                            notes_open.set(true);
+                                            editing_notes.set(true);
+                                        },
+                                        div { class: "flex justify-center items-center",
                                             img {
-                                                class: "h-6 w-6 dark:invert",
-                                                src: asset!("public/assets/chevron-top.svg")
+                                                class: "h-6 w-6 dark:invert object-contain",
+                                                src: asset!("public/assets/notes.svg")
                                             }
                                         }
-                                        button {
-                                            class: "block btn content-center join-item tooltip",
-                                            "data-tip": "Move Bottom",
-                                            onclick: move |_| { onmovebottom(counter.id) },
+                                    }
+
+                                    button {
+                                        class: "btn tooltip",
+                                        "data-tip": "History",
+                                        onclick: move |_| {
+                                            history_open.set(true);
+                                            document::eval(&format!("document.getElementById('history-{}').showModal()", counter.id));
+                                        },
+                                        div { class: "flex justify-center items-center",
                                             img {
-                                                class: "h-6 w-6 dark:invert",
-                                                src: asset!("public/assets/chevron-bottom.svg")
+                                                class: "h-6 w-6 dark:invert object-contain",
+                                                src: asset!("public/assets/clock.svg")
                                             }
                                         }
                                     }
@@ -179,6 +614,77 @@ pub fn TallyCounter(
                         }
                     }
                 }
+                if !tags.is_empty() {
+                    div { class: "flex flex-wrap gap-1 px-2 pt-1",
+                        for tag in tags.clone() {
+                            span { class: "badge badge-sm badge-outline", "{tag}" }
+                        }
+                    }
+                }
+                if let Some(goal) = limits.goal.filter(|goal| *goal > 0) {
+                    div { class: "flex flex-row items-center gap-2 px-2 pt-1",
+                        progress {
+                            class: if counter.goal_reached() { "progress progress-success w-full" } else { "progress progress-primary w-full" },
+                            value: counter.count.clamp(0, goal).to_string(),
+                            max: goal.to_string(),
+                        }
+                        if counter.goal_reached() {
+                            span { class: "badge badge-success whitespace-nowrap", "Goal reached" }
+                        } else {
+                            span { class: "text-xs whitespace-nowrap",
+                                {format!("{} / {}", kind.format(counter.count), kind.format(goal))}
+                            }
+                        }
+                    }
+                }
+                if !notes.is_empty() && !editing_notes() {
+                    button {
+                        class: "btn btn-ghost btn-xs self-start mt-1 {text_color}",
+                        aria_expanded: notes_open(),
+                        onclick: move |_| notes_open.set(!notes_open()),
+                        if notes_open() { "Hide notes" } else { "Show notes" }
+                    }
+                }
+                if notes_open() {
+                    div { class: "px-2 pt-1",
+                        if editing_notes() {
+                            textarea {
+                                class: "textarea textarea-bordered w-full bg-base-100 text-base-content",
+                                rows: 4,
+                                placeholder: "Notes, formatted with Markdown",
+                                value: "{notes}",
+                                oninput: move |e| onnoteschange((counter.id, e.value())),
+                            }
+                            div { class: "flex justify-end",
+                                button {
+                                    class: "btn btn-ghost btn-xs {text_color}",
+                                    onclick: {
+                                        let is_empty = notes.is_empty();
+                                        move |_| {
+                                            editing_notes.set(false);
+                                            if is_empty {
+                                                notes_open.set(false);
+                                            }
+                                        }
+                                    },
+                                    "Done"
+                                }
+                            }
+                        } else {
+                            div {
+                                class: "prose prose-sm max-w-none dark:prose-invert {text_color}",
+                                dangerous_inner_html: markdown::to_html(&notes),
+                            }
+                            div { class: "flex justify-end",
+                                button {
+                                    class: "btn btn-ghost btn-xs {text_color}",
+                                    onclick: move |_| editing_notes.set(true),
+                                    "Edit"
+                                }
+                            }
+                        }
+                    }
+                }
             }
         }
     }
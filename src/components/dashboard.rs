@@ -0,0 +1,385 @@
+use std::rc::Rc;
+
+use chrono::{DateTime, Local, Utc};
+use dioxus::prelude::*;
+use enum_iterator::all;
+use uuid::Uuid;
+
+use crate::{
+    chart::{self, Breakdown, Interval, Series, TimeRange},
+    color::Rgb,
+    components::navbar::Navbar,
+    models::Counter,
+    state::AppState,
+    storage::{self, Page, Store},
+    Route,
+};
+
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 200.0;
+/// Room left of the plot for the value axis.
+const AXIS_WIDTH: f64 = 64.0;
+/// Room below the plot for the time axis.
+const LABEL_HEIGHT: f64 = 20.0;
+const PLOT_HEIGHT: f64 = HEIGHT - LABEL_HEIGHT;
+const PLOT_WIDTH: f64 = WIDTH - AXIS_WIDTH;
+const BAR_ROW_HEIGHT: f64 = 28.0;
+/// At most this many time labels are shown under a chart.
+const MAX_TIME_LABELS: usize = 6;
+
+/// Where `value` sits between `low` and `high`, from 0 to 1.
+#[allow(clippy::cast_precision_loss)]
+fn fraction(value: i64, low: i64, high: i64) -> f64 {
+    let span = i128::from(high) - i128::from(low);
+    if span == 0 {
+        return 0.5;
+    }
+    (i128::from(value) - i128::from(low)) as f64 / span as f64
+}
+
+fn count(len: usize) -> f64 {
+    f64::from(u32::try_from(len).unwrap_or(u32::MAX))
+}
+
+fn format_bucket(at: DateTime<Utc>, interval: Interval) -> String {
+    at.with_timezone(&Local)
+        .format(interval.format())
+        .to_string()
+}
+
+/// Trends over time for one counter or all of them, drawn as inline SVG so
+/// nothing has to be fetched.
+#[component]
+pub fn Dashboard() -> Element {
+    let state = use_context::<AppState>();
+    let store = use_context::<Rc<dyn Store>>();
+
+    let mut range = use_signal(TimeRange::default);
+    let mut selected = use_signal(|| None::<Uuid>);
+
+    let counters = (state.counters)();
+    let selection = selected().and_then(|id| counters.iter().find(|counter| counter.id == id));
+    let charted = selection.map_or_else(|| counters.clone(), |counter| vec![counter.clone()]);
+
+    let interval = range().interval();
+    let buckets = range().buckets(Local::now());
+    let since = buckets.first().copied().unwrap_or_else(Utc::now);
+
+    // Only the changes within the range are read. Read again after every
+    // save, which may have added to them.
+    let _ = (state.revision)();
+    let (charted, history_error) = match charted
+        .into_iter()
+        .map(|counter| {
+            let mut events =
+                store.events(counter.id, since..=*storage::all_time().end(), Page::ALL)?;
+            events.reverse();
+            Ok(Counter { events, ..counter })
+        })
+        .collect::<Result<Vec<_>, storage::StorageError>>()
+    {
+        Ok(charted) => (charted, None),
+        Err(err) => (Vec::new(), Some(err.to_string())),
+    };
+    let activity = chart::activity(&charted, &buckets);
+    let running_total = chart::running_total(&charted, &buckets);
+    let breakdown = chart::by_color(&charted, since);
+    let net_change = activity
+        .points
+        .iter()
+        .fold(0_i64, |sum, point| sum.saturating_add(point.value));
+    let fill = selection.map_or_else(|| String::from("currentColor"), series_color);
+
+    rsx! {
+        Navbar {
+            start_content: rsx! {
+                Link { class: "btn btn-ghost", to: Route::Home {},
+                    img {
+                        class: "h-5 w-5 dark:invert -rotate-90",
+                        src: asset!("public/assets/chevron-up.svg"),
+                    }
+                    "Counters"
+                }
+            },
+            center_content: rsx! {
+                p { class: "text-xl select-none", "Dashboard" }
+            },
+        }
+
+        div { class: "flex flex-col gap-6 max-w-[48rem] mx-auto px-6 pb-10",
+            div { class: "flex flex-row flex-wrap gap-2",
+                select {
+                    class: "select select-bordered select-sm",
+                    aria_label: "Counter to chart",
+                    onchange: move |e| selected.set(Uuid::parse_str(&e.value()).ok()),
+                    option { value: "", selected: selection.is_none(), "All counters" }
+                    for counter in counters.iter() {
+                        option {
+                            value: "{counter.id}",
+                            selected: selected() == Some(counter.id),
+                            "{counter.title}"
+                        }
+                    }
+                }
+                select {
+                    class: "select select-bordered select-sm",
+                    aria_label: "Time range",
+                    onchange: move |e| {
+                        if let Some(choice) = all::<TimeRange>().find(|choice| format!("{choice:?}") == e.value()) {
+                            range.set(choice);
+                        }
+                    },
+                    for choice in all::<TimeRange>() {
+                        option {
+                            value: "{choice:?}",
+                            selected: range() == choice,
+                            "{choice.label()}"
+                        }
+                    }
+                }
+            }
+
+            if let Some(err) = history_error {
+                div { role: "alert", class: "alert alert-error",
+                    span { "The history could not be read: {err}" }
+                }
+            } else if charted.is_empty() {
+                p { class: "text-sm opacity-70", "Add a counter to see its trends here." }
+            } else {
+                div { class: "stats shadow",
+                    div { class: "stat",
+                        div { class: "stat-title", "Net change" }
+                        div { class: "stat-value", "{activity.kind.format_delta(net_change)}" }
+                        div { class: "stat-desc", "{range().label()}" }
+                    }
+                    div { class: "stat",
+                        div { class: "stat-title", "Total now" }
+                        div { class: "stat-value",
+                            {
+                                running_total
+                                    .points
+                                    .last()
+                                    .map(|point| running_total.kind.format(point.value))
+                            }
+                        }
+                    }
+                }
+
+                section {
+                    h2 { class: "text-lg font-semibold mb-2", "Change {interval.label()}" }
+                    BarChart { series: activity, interval, fill: fill.clone() }
+                }
+
+                section {
+                    h2 { class: "text-lg font-semibold mb-2", "Running total" }
+                    LineChart { series: running_total, interval, stroke: fill }
+                }
+
+                if selection.is_none() {
+                    section {
+                        h2 { class: "text-lg font-semibold mb-2", "Change by color" }
+                        ColorBreakdown { breakdown }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn series_color(counter: &Counter) -> String {
+    counter
+        .custom_color
+        .or_else(|| counter.color.rgb())
+        .map_or_else(|| String::from("currentColor"), Rgb::to_hex)
+}
+
+#[component]
+fn TimeAxis(series: Series, interval: Interval) -> Element {
+    let slot = PLOT_WIDTH / count(series.points.len()).max(1.0);
+    let every = series.points.len().div_ceil(MAX_TIME_LABELS).max(1);
+
+    rsx! {
+        for (index, point) in series.points.iter().enumerate().filter(|(index, _)| index % every == 0) {
+            text {
+                x: AXIS_WIDTH + slot * (count(index) + 0.5),
+                y: HEIGHT - 4.0,
+                text_anchor: "middle",
+                font_size: 11,
+                fill: "currentColor",
+                fill_opacity: 0.7,
+                {format_bucket(point.at, interval)}
+            }
+        }
+    }
+}
+
+#[component]
+fn ValueAxis(series: Series, low: i64, high: i64) -> Element {
+    rsx! {
+        text {
+            x: AXIS_WIDTH - 6.0,
+            y: 11.0,
+            text_anchor: "end",
+            font_size: 11,
+            fill: "currentColor",
+            fill_opacity: 0.7,
+            {series.kind.format(high)}
+        }
+        text {
+            x: AXIS_WIDTH - 6.0,
+            y: PLOT_HEIGHT,
+            text_anchor: "end",
+            font_size: 11,
+            fill: "currentColor",
+            fill_opacity: 0.7,
+            {series.kind.format(low)}
+        }
+    }
+}
+
+/// One bar per bucket, growing up or down from zero.
+#[component]
+fn BarChart(series: Series, interval: Interval, fill: String) -> Element {
+    let low = series
+        .points
+        .iter()
+        .map(|point| point.value)
+        .min()
+        .unwrap_or(0)
+        .min(0);
+    let high = series
+        .points
+        .iter()
+        .map(|point| point.value)
+        .max()
+        .unwrap_or(0)
+        .max(0);
+    let y = move |value: i64| PLOT_HEIGHT * (1.0 - fraction(value, low, high));
+    let slot = PLOT_WIDTH / count(series.points.len()).max(1.0);
+
+    rsx! {
+        svg {
+            class: "w-full h-auto",
+            view_box: "0 0 {WIDTH} {HEIGHT}",
+            role: "img",
+            ValueAxis { series: series.clone(), low, high }
+            line {
+                x1: AXIS_WIDTH,
+                x2: WIDTH,
+                y1: y(0),
+                y2: y(0),
+                stroke: "currentColor",
+                stroke_opacity: 0.3,
+            }
+            for (index, point) in series.points.iter().enumerate() {
+                rect {
+                    x: AXIS_WIDTH + slot * count(index) + slot * 0.1,
+                    y: y(point.value.max(0)),
+                    width: slot * 0.8,
+                    height: (y(point.value.min(0)) - y(point.value.max(0))).max(0.0),
+                    fill: "{fill}",
+                    title {
+                        "{format_bucket(point.at, interval)}: {series.kind.format_delta(point.value)}"
+                    }
+                }
+            }
+            TimeAxis { series, interval }
+        }
+    }
+}
+
+/// The series as a line through the end of each bucket.
+#[component]
+fn LineChart(series: Series, interval: Interval, stroke: String) -> Element {
+    let low = series
+        .points
+        .iter()
+        .map(|point| point.value)
+        .min()
+        .unwrap_or(0);
+    let high = series
+        .points
+        .iter()
+        .map(|point| point.value)
+        .max()
+        .unwrap_or(0);
+    let slot = PLOT_WIDTH / count(series.points.len()).max(1.0);
+    let points = series
+        .points
+        .iter()
+        .enumerate()
+        .map(|(index, point)| {
+            format!(
+                "{:.1},{:.1}",
+                AXIS_WIDTH + slot * (count(index) + 0.5),
+                PLOT_HEIGHT * (1.0 - fraction(point.value, low, high)),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    rsx! {
+        svg {
+            class: "w-full h-auto",
+            view_box: "0 0 {WIDTH} {HEIGHT}",
+            role: "img",
+            ValueAxis { series: series.clone(), low, high }
+            polyline {
+                points,
+                fill: "none",
+                stroke: "{stroke}",
+                stroke_width: 2,
+                stroke_linejoin: "round",
+            }
+            TimeAxis { series, interval }
+        }
+    }
+}
+
+/// A horizontal bar per color, sized by the size of its net change.
+#[component]
+fn ColorBreakdown(breakdown: Breakdown) -> Element {
+    let label_width = 80.0;
+    let value_width = 80.0;
+    let largest = breakdown
+        .slices
+        .iter()
+        .map(|(_, change)| change.saturating_abs())
+        .max()
+        .unwrap_or(0);
+    let height = BAR_ROW_HEIGHT * count(breakdown.slices.len());
+
+    rsx! {
+        svg {
+            class: "w-full h-auto",
+            view_box: "0 0 {WIDTH} {height}",
+            role: "img",
+            for (index, (color, change)) in breakdown.slices.iter().enumerate() {
+                text {
+                    x: 0,
+                    y: BAR_ROW_HEIGHT.mul_add(count(index), 18.0),
+                    font_size: 13,
+                    fill: "currentColor",
+                    "{color:?}"
+                }
+                rect {
+                    x: label_width,
+                    y: BAR_ROW_HEIGHT.mul_add(count(index), 4.0),
+                    width: (WIDTH - label_width - value_width)
+                        * fraction(change.saturating_abs(), 0, largest.max(1)),
+                    height: BAR_ROW_HEIGHT - 8.0,
+                    rx: 3,
+                    fill: color.rgb().map_or_else(|| String::from("currentColor"), Rgb::to_hex),
+                }
+                text {
+                    x: WIDTH,
+                    y: BAR_ROW_HEIGHT.mul_add(count(index), 18.0),
+                    text_anchor: "end",
+                    font_size: 13,
+                    fill: "currentColor",
+                    {breakdown.kind.format_delta(*change)}
+                }
+            }
+        }
+    }
+}
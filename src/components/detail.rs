@@ -0,0 +1,347 @@
+use std::rc::Rc;
+
+use chrono::{DateTime, Local, Utc};
+use dioxus::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    components::{
+        event_log::EventLog, navbar::Navbar, schedule_editor::ScheduleEditor,
+        value_input::ValueInput,
+    },
+    history::Coalesce,
+    icon::Icon,
+    markdown,
+    models::{Counter, Limits},
+    state::AppState,
+    stats::CounterStats,
+    storage::Store,
+    Route,
+};
+
+/// Everything about one counter: its statistics, settings, notes and full history.
+#[component]
+pub fn CounterDetail(id: ReadOnlySignal<Uuid>) -> Element {
+    let id = id();
+    let state = use_context::<AppState>();
+
+    let mut editing_notes = use_signal(|| false);
+
+    let Some(counter) = (state.counters)()
+        .into_iter()
+        .find(|counter| counter.id == id)
+    else {
+        return rsx! {
+            div { class: "flex flex-col items-center gap-4 my-14",
+                p { "This counter no longer exists." }
+                Link { class: "btn", to: Route::Home {}, "Back to counters" }
+            }
+        };
+    };
+
+    rsx! {
+        div {
+            class: "min-h-screen outline-none",
+            tabindex: 0,
+            onmounted: move |e| async move {
+                _ = e.set_focus(true).await;
+            },
+            onkeydown: move |e| state.handle_shortcut(&e),
+            Navbar {
+                start_content: rsx! {
+                    Link { class: "btn btn-ghost", to: Route::Home {},
+                        img {
+                            class: "h-5 w-5 dark:invert -rotate-90",
+                            src: asset!("public/assets/chevron-up.svg"),
+                        }
+                        "Counters"
+                    }
+                },
+                center_content: rsx! {
+                    p { class: "text-xl select-none flex items-center gap-2",
+                        match &counter.icon {
+                            Some(Icon::Bundled(bundled)) => rsx! {
+                                img { class: "h-6 w-6 dark:invert", alt: bundled.label(), src: bundled.asset() }
+                            },
+                            Some(Icon::Emoji(emoji)) => rsx! {
+                                span { "{emoji}" }
+                            },
+                            None => rsx! {},
+                        }
+                        "{counter.title}"
+                    }
+                },
+            }
+
+            div { class: "flex flex-col gap-6 max-w-[48rem] mx-auto px-6 pb-10",
+                CounterSummary { counter: counter.clone() }
+
+                CounterSettings { counter: counter.clone() }
+
+                section {
+                    div { class: "flex flex-row items-center justify-between mb-2",
+                        h2 { class: "text-lg font-semibold", "Notes" }
+                        button {
+                            class: "btn btn-ghost btn-sm",
+                            onclick: move |_| editing_notes.set(!editing_notes()),
+                            if editing_notes() { "Done" } else { "Edit" }
+                        }
+                    }
+                    if editing_notes() {
+                        textarea {
+                            class: "textarea textarea-bordered w-full",
+                            rows: 8,
+                            placeholder: "Notes, formatted with Markdown",
+                            value: "{counter.notes}",
+                            oninput: move |e| {
+                                let notes = e.value();
+                                state.update(id, Some(Coalesce::Notes(id)), |counter| Counter {
+                                    notes: notes.clone(),
+                                    ..counter
+                                });
+                            },
+                        }
+                    } else if counter.notes.is_empty() {
+                        p { class: "text-sm opacity-70", "No notes yet." }
+                    } else {
+                        div {
+                            class: "prose max-w-none dark:prose-invert",
+                            dangerous_inner_html: markdown::to_html(&counter.notes),
+                        }
+                    }
+                }
+
+                section {
+                    EventLog { counter }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn CounterSummary(counter: ReadOnlySignal<Counter>) -> Element {
+    let counter = counter();
+    let revision = use_context::<AppState>().revision;
+    let store = use_context::<Rc<dyn Store>>();
+    let kind = counter.kind;
+
+    // Read again after every save, which may have added to the history.
+    let _ = revision();
+    let (stats, history_error) = match store.stats(counter.id) {
+        Ok(stats) => (stats, None),
+        Err(err) => (CounterStats::default(), Some(err.to_string())),
+    };
+
+    let format_time = |at: Option<DateTime<Utc>>| {
+        at.map_or_else(
+            || String::from("never"),
+            |at| {
+                at.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            },
+        )
+    };
+
+    rsx! {
+        if let Some(err) = history_error {
+            div { role: "alert", class: "alert alert-error",
+                span { "The history could not be read: {err}" }
+            }
+        }
+        div { class: "stats stats-vertical sm:stats-horizontal shadow",
+            div { class: "stat",
+                div { class: "stat-title", "Value" }
+                div { class: "stat-value", "{kind.format(counter.count)}" }
+                if let Some(goal) = counter.limits.goal {
+                    div { class: "stat-desc", "Goal {kind.format(goal)}" }
+                }
+            }
+            div { class: "stat",
+                div { class: "stat-title", "Changes" }
+                div { class: "stat-value", "{stats.events}" }
+                div { class: "stat-desc",
+                    "+{kind.format(stats.added)} / −{kind.format(stats.removed)}"
+                }
+            }
+            div { class: "stat",
+                div { class: "stat-title", "Sessions" }
+                div { class: "stat-value", "{stats.sessions}" }
+                if let (Some(average), Some(best)) = (stats.session_average, stats.session_best) {
+                    div { class: "stat-desc",
+                        "Average {kind.format(average)}, best {kind.format(best)}"
+                    }
+                }
+            }
+        }
+
+        table { class: "table table-sm",
+            tbody {
+                tr {
+                    th { "Increments" }
+                    td { class: "text-end", "{stats.increments}" }
+                }
+                tr {
+                    th { "Decrements" }
+                    td { class: "text-end", "{stats.decrements}" }
+                }
+                tr {
+                    th { "Edits" }
+                    td { class: "text-end", "{stats.edits}" }
+                }
+                tr {
+                    th { "Resets" }
+                    td { class: "text-end", "{stats.resets}" }
+                }
+                tr {
+                    th { "First change" }
+                    td { class: "text-end", "{format_time(stats.first_change)}" }
+                }
+                tr {
+                    th { "Last change" }
+                    td { class: "text-end", "{format_time(stats.last_change)}" }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn CounterSettings(counter: ReadOnlySignal<Counter>) -> Element {
+    let counter = counter();
+    let state = use_context::<AppState>();
+    let id = counter.id;
+    let kind = counter.kind;
+
+    rsx! {
+        section {
+            h2 { class: "text-lg font-semibold mb-2", "Settings" }
+            div { class: "grid grid-cols-1 sm:grid-cols-2 gap-2",
+                label { class: "form-control sm:col-span-2",
+                    div { class: "label",
+                        span { class: "label-text", "Title" }
+                    }
+                    input {
+                        class: "input input-bordered input-sm w-full",
+                        r#type: "text",
+                        value: "{counter.title}",
+                        oninput: move |e| {
+                            let title = e.value();
+                            state.update(id, Some(Coalesce::Title(id)), |counter| Counter {
+                                title: title.clone(),
+                                ..counter
+                            });
+                        },
+                    }
+                }
+                label { class: "form-control",
+                    div { class: "label",
+                        span { class: "label-text", "Step" }
+                    }
+                    ValueInput {
+                        input_class: "input input-bordered input-sm w-full",
+                        kind,
+                        value: counter.step,
+                        onchange: move |step: Option<i64>| {
+                            if let Some(step) = step.filter(|step| *step > 0) {
+                                state.update(id, Some(Coalesce::Step(id)), |counter| Counter { step, ..counter });
+                            }
+                        },
+                    }
+                }
+                label { class: "form-control",
+                    div { class: "label",
+                        span { class: "label-text", "Reset to" }
+                    }
+                    ValueInput {
+                        input_class: "input input-bordered input-sm w-full",
+                        kind,
+                        value: counter.reset_value,
+                        onchange: move |reset_value: Option<i64>| {
+                            if let Some(reset_value) = reset_value {
+                                state.update(id, Some(Coalesce::ResetValue(id)), |counter| Counter {
+                                    reset_value,
+                                    ..counter
+                                });
+                            }
+                        },
+                    }
+                }
+                LimitSettings { counter: counter.clone() }
+                label { class: "form-control",
+                    div { class: "label",
+                        span { class: "label-text", "Reset automatically" }
+                    }
+                    ScheduleEditor {
+                        schedule: counter.schedule,
+                        onchange: move |schedule| {
+                            state.update(id, Some(Coalesce::Schedule(id)), |counter| Counter { schedule, ..counter });
+                        },
+                    }
+                }
+            }
+            if !counter.tags.is_empty() {
+                div { class: "flex flex-wrap gap-1 mt-2",
+                    for tag in counter.tags.clone() {
+                        span { class: "badge badge-outline", "{tag}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn LimitSettings(counter: ReadOnlySignal<Counter>) -> Element {
+    let counter = counter();
+    let state = use_context::<AppState>();
+    let id = counter.id;
+    let kind = counter.kind;
+    let limits = counter.limits;
+
+    rsx! {
+        label { class: "form-control",
+            div { class: "label",
+                span { class: "label-text", "Min" }
+            }
+            ValueInput {
+                input_class: "input input-bordered input-sm w-full",
+                kind,
+                value: limits.min,
+                optional: true,
+                onchange: move |min| {
+                    state.update(id, Some(Coalesce::Limits(id)), |counter| counter.with_limits(Limits { min, ..limits }));
+                },
+            }
+        }
+        label { class: "form-control",
+            div { class: "label",
+                span { class: "label-text", "Max" }
+            }
+            ValueInput {
+                input_class: "input input-bordered input-sm w-full",
+                kind,
+                value: limits.max,
+                optional: true,
+                onchange: move |max| {
+                    state.update(id, Some(Coalesce::Limits(id)), |counter| counter.with_limits(Limits { max, ..limits }));
+                },
+            }
+        }
+        label { class: "form-control",
+            div { class: "label",
+                span { class: "label-text", "Goal" }
+            }
+            ValueInput {
+                input_class: "input input-bordered input-sm w-full",
+                kind,
+                value: limits.goal,
+                optional: true,
+                onchange: move |goal| {
+                    state.update(id, Some(Coalesce::Limits(id)), |counter| counter.with_limits(Limits { goal, ..limits }));
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::rc::Rc;
+
+use chrono::{DateTime, Local, Utc};
+use dioxus::prelude::*;
+
+use crate::{
+    models::{Counter, EventKind},
+    state::AppState,
+    storage::{self, Page, Store},
+};
+
+/// Entries shown at first, and added by each "Show more".
+const PAGE_SIZE: usize = 50;
+
+const fn kind_label(kind: EventKind) -> &'static str {
+    match kind {
+        EventKind::Increment => "Increment",
+        EventKind::Decrement => "Decrement",
+        EventKind::Set => "Set",
+        EventKind::Reset => "Reset",
+        EventKind::Undo => "Undo",
+        EventKind::Redo => "Redo",
+    }
+}
+
+fn format_time(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// The counter's changes and sessions, newest first, read from the store a
+/// page at a time.
+#[component]
+pub fn EventLog(counter: ReadOnlySignal<Counter>) -> Element {
+    let state = use_context::<AppState>();
+    let store = use_context::<Rc<dyn Store>>();
+
+    let mut shown_events = use_signal(|| PAGE_SIZE);
+    let mut shown_sessions = use_signal(|| PAGE_SIZE);
+
+    // Read again after every save, which may have added to the history, and
+    // when more is shown. One entry past the page tells whether there are more.
+    let id = use_memo(move || counter.read().id);
+    let history = use_memo(move || {
+        let _ = (state.revision)();
+        store
+            .events(id(), storage::all_time(), Page::first(shown_events() + 1))
+            .and_then(|events| {
+                let sessions = store.sessions(id(), Page::first(shown_sessions() + 1))?;
+                Ok((events, sessions))
+            })
+            .map_err(|err| err.to_string())
+    });
+
+    let counter = counter();
+    let (mut events, mut sessions) = match history() {
+        Ok(history) => history,
+        Err(err) => {
+            return rsx! {
+                div { role: "alert", class: "alert alert-error",
+                    span { "The history could not be read: {err}" }
+                }
+            };
+        }
+    };
+    let more_events = events.len() > shown_events();
+    let more_sessions = sessions.len() > shown_sessions();
+    events.truncate(shown_events());
+    sessions.truncate(shown_sessions());
+
+    rsx! {
+        h2 { class: "text-lg font-semibold mb-2", "{counter.title}" }
+        if events.is_empty() {
+            p { class: "text-sm opacity-70", "No changes recorded yet." }
+        } else {
+            div { class: "overflow-y-auto max-h-96",
+                table { class: "table table-zebra table-sm",
+                    thead {
+                        tr {
+                            th { "When" }
+                            th { "Change" }
+                            th { class: "text-end", "Delta" }
+                            th { class: "text-end", "Value" }
+                        }
+                    }
+                    tbody {
+                        for event in events {
+                            tr {
+                                td { {format_time(event.at)} }
+                                td { {kind_label(event.kind)} }
+                                td { class: "text-end", {counter.kind.format_delta(event.delta)} }
+                                td { class: "text-end", {counter.kind.format(event.value)} }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if more_events {
+            button {
+                class: "btn btn-ghost btn-sm mt-2",
+                onclick: move |_| shown_events += PAGE_SIZE,
+                "Show more"
+            }
+        }
+        if !sessions.is_empty() {
+            h3 { class: "text-md font-semibold mt-4 mb-2", "Sessions" }
+            div { class: "overflow-y-auto max-h-64",
+                table { class: "table table-zebra table-sm",
+                    thead {
+                        tr {
+                            th { "Started" }
+                            th { "Ended" }
+                            th { class: "text-end", "Final value" }
+                        }
+                    }
+                    tbody {
+                        for session in sessions {
+                            tr {
+                                td { {session.started_at.map_or_else(|| String::from("—"), format_time)} }
+                                td { {format_time(session.ended_at)} }
+                                td { class: "text-end", {counter.kind.format(session.value)} }
+                            }
+                        }
+                    }
+                }
+            }
+            if more_sessions {
+                button {
+                    class: "btn btn-ghost btn-sm mt-2",
+                    onclick: move |_| shown_sessions += PAGE_SIZE,
+                    "Show more"
+                }
+            }
+        }
+    }
+}
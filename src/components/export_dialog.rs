@@ -0,0 +1,119 @@
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use dioxus_logger::tracing::error;
+
+use crate::{components::modal::Modal, export, state::AppState, storage::Store};
+
+/// Navbar button that saves every counter, and optionally its history, as CSV.
+#[component]
+pub fn ExportButton() -> Element {
+    let state = use_context::<AppState>();
+    let store = use_context::<Rc<dyn Store>>();
+
+    let mut with_history = use_signal(|| false);
+    let mut status = use_signal(|| None::<Result<String, String>>);
+
+    let handle_export = move |_| {
+        let store = store.clone();
+        spawn(async move {
+            let Some(file) = rfd::AsyncFileDialog::new()
+                .set_title("Export counters")
+                .set_file_name("counters.csv")
+                .add_filter("CSV", &["csv"])
+                .save_file()
+                .await
+            else {
+                return;
+            };
+
+            // The history goes to a second file the user picks too, so the
+            // save dialog can warn before anything is overwritten.
+            let events = if with_history() {
+                let mut dialog = rfd::AsyncFileDialog::new()
+                    .set_title("Export history")
+                    .set_file_name(export::events_file_name(file.path()))
+                    .add_filter("CSV", &["csv"]);
+                if let Some(directory) = file.path().parent() {
+                    dialog = dialog.set_directory(directory);
+                }
+                let Some(events) = dialog.save_file().await else {
+                    return;
+                };
+                Some(events)
+            } else {
+                None
+            };
+
+            let result = export::write(
+                &*store,
+                file.path(),
+                events.as_ref().map(rfd::FileHandle::path),
+                &(state.counters)(),
+                &(state.groups)(),
+            );
+            match result {
+                Ok(written) => {
+                    let names = written
+                        .iter()
+                        .filter_map(|path| path.file_name())
+                        .map(|name| name.to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join(" and ");
+                    status.set(Some(Ok(format!("Exported {names}"))));
+                }
+                Err(err) => {
+                    error!("failed to export counters: {err}");
+                    status.set(Some(Err(err.to_string())));
+                }
+            }
+        });
+    };
+
+    rsx! {
+        button {
+            class: "btn btn-ghost btn-circle tooltip tooltip-bottom",
+            "data-tip": "Export as CSV",
+            disabled: (state.counters)().is_empty(),
+            "onclick": "export_modal.showModal()",
+            img {
+                class: "w-1/2 h-1/2 dark:invert",
+                src: asset!("public/assets/download.svg"),
+            }
+        }
+        Modal { id: "export_modal",
+            h1 { class: "text-lg", "Export {(state.counters)().len()} counters" }
+            p { class: "py-2 text-sm",
+                "Saves a spreadsheet with one row per counter and its current settings."
+            }
+            label { class: "label cursor-pointer justify-start gap-2",
+                input {
+                    class: "checkbox checkbox-sm",
+                    r#type: "checkbox",
+                    checked: with_history(),
+                    onchange: move |e| with_history.set(e.checked()),
+                }
+                span { class: "label-text", "Also export the full history, one row per change, to a second file you choose next" }
+            }
+            div { class: "modal-action",
+                button { class: "btn", "Cancel" }
+                button { class: "btn btn-primary", onclick: handle_export, "Export…" }
+            }
+        }
+        if let Some(result) = status() {
+            div { class: "toast toast-end z-10",
+                div {
+                    role: "alert",
+                    class: if result.is_ok() { "alert alert-success" } else { "alert alert-error" },
+                    span {
+                        match &result {
+                            Ok(message) => message.clone(),
+                            Err(err) => format!("Export failed: {err}"),
+                        }
+                    }
+                    button { class: "btn btn-sm btn-ghost", onclick: move |_| status.set(None), "✕" }
+                }
+            }
+        }
+    }
+}
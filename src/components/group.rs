@@ -0,0 +1,60 @@
+use dioxus::prelude::*;
+use uuid::Uuid;
+
+use crate::models::Group;
+
+/// Title bar of a group section: collapse toggle, editable name, number of
+/// counters and their subtotal, and buttons to add a counter or remove the group.
+#[component]
+pub fn GroupHeader(
+    group: Group,
+    /// Number of counters shown in the section.
+    size: usize,
+    /// Formatted sum of the counters' values.
+    subtotal: String,
+    ontoggle: EventHandler<Uuid>,
+    onrename: EventHandler<(Uuid, String)>,
+    onaddcounter: EventHandler<Uuid>,
+    ondelete: EventHandler<Uuid>,
+) -> Element {
+    rsx! {
+        div { class: "flex flex-row items-center gap-2 max-w-[36rem] mx-14 mt-4 border-b border-base-300",
+            button {
+                class: "btn btn-ghost btn-square btn-sm",
+                aria_label: if group.collapsed { "Expand {group.name}" } else { "Collapse {group.name}" },
+                aria_expanded: !group.collapsed,
+                onclick: move |_| ontoggle(group.id),
+                img {
+                    class: "h-5 w-5 dark:invert",
+                    src: if group.collapsed { asset!("public/assets/chevron-down.svg") } else { asset!("public/assets/chevron-up.svg") },
+                }
+            }
+            input {
+                class: "grow bg-transparent font-semibold",
+                r#type: "text",
+                value: group.name,
+                oninput: move |e| onrename((group.id, e.value())),
+            }
+            span { class: "badge badge-ghost", "{size}" }
+            span { class: "tabular-nums", title: "Subtotal", "Σ {subtotal}" }
+            button {
+                class: "btn btn-ghost btn-circle btn-sm tooltip",
+                "data-tip": "Add counter",
+                onclick: move |_| onaddcounter(group.id),
+                img {
+                    class: "h-5 w-5 dark:invert",
+                    src: asset!("public/assets/plus.svg"),
+                }
+            }
+            button {
+                class: "btn btn-ghost btn-circle btn-sm tooltip",
+                "data-tip": "Remove group, keeping its counters. This can't be undone",
+                onclick: move |_| ondelete(group.id),
+                img {
+                    class: "h-5 w-5 dark:invert",
+                    src: asset!("public/assets/trash.svg"),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use dioxus::prelude::*;
+use dioxus_logger::tracing::error;
+use enum_iterator::all;
+
+use crate::{
+    color::Rgb,
+    components::modal::Modal,
+    import::{self, Field, Mapping, Mode, Table, PREVIEW_ROWS},
+    state::AppState,
+};
+
+/// Navbar button that creates counters from a CSV or JSON file. The file is
+/// read first, then the columns are mapped and the result previewed before
+/// anything changes.
+#[component]
+pub fn ImportButton() -> Element {
+    let state = use_context::<AppState>();
+
+    let mut table = use_signal(|| None::<Table>);
+    let mut file_name = use_signal(String::new);
+    let mut mapping = use_signal(Mapping::new);
+    let mut mode = use_signal(Mode::default);
+    let mut status = use_signal(|| None::<Result<String, String>>);
+
+    let drafts = use_memo(move || {
+        table
+            .read()
+            .as_ref()
+            .map(|table| table.drafts(&mapping.read()))
+            .unwrap_or_default()
+    });
+    // Whether a row fits depends on the counters it merges into, so every
+    // row is checked against what importing it would do.
+    let outcome = use_memo(move || import::apply((state.counters)(), drafts(), mode()));
+
+    let handle_pick = move |_| {
+        spawn(async move {
+            let Some(file) = rfd::AsyncFileDialog::new()
+                .set_title("Import counters")
+                .add_filter("CSV or JSON", &["csv", "json"])
+                .pick_file()
+                .await
+            else {
+                return;
+            };
+
+            let parsed = String::from_utf8(file.read().await)
+                .map_err(|_| String::from("the file is not UTF-8 text"))
+                .and_then(|contents| Table::parse(&contents).map_err(|err| err.to_string()));
+            match parsed {
+                Ok(parsed) => {
+                    mapping.set(parsed.guess_mapping());
+                    table.set(Some(parsed));
+                    file_name.set(file.file_name());
+                    status.set(None);
+                    document::eval("import_modal.showModal()");
+                }
+                Err(err) => {
+                    error!("failed to read import file: {err}");
+                    status.set(Some(Err(err)));
+                }
+            }
+        });
+    };
+
+    let handle_import = move |_| {
+        let outcome = outcome();
+        let imported = outcome.rows.iter().filter(|row| row.is_ok()).count();
+        let skipped = outcome.rows.len() - imported;
+        state.commit(outcome.counters, None);
+        table.set(None);
+
+        let message = if skipped == 0 {
+            format!("Imported {imported} counters")
+        } else {
+            format!("Imported {imported} counters, skipped {skipped} rows with errors")
+        };
+        status.set(Some(Ok(message)));
+    };
+
+    let headers = table
+        .read()
+        .as_ref()
+        .map(|table| table.headers.clone())
+        .unwrap_or_default();
+    let errors = outcome()
+        .rows
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, draft)| draft.err().map(|err| (index + 1, err)))
+        .collect::<Vec<_>>();
+    let importable = drafts().len() - errors.len();
+
+    rsx! {
+        button {
+            class: "btn btn-ghost btn-circle tooltip tooltip-bottom",
+            "data-tip": "Import from CSV or JSON",
+            onclick: handle_pick,
+            img {
+                class: "w-1/2 h-1/2 dark:invert",
+                src: asset!("public/assets/upload.svg"),
+            }
+        }
+        Modal { id: "import_modal",
+            h1 { class: "text-lg", "Import from {file_name}" }
+            h2 { class: "font-semibold mt-4 mb-1", "Columns" }
+            div { class: "grid grid-cols-2 gap-2",
+                for field in all::<Field>() {
+                    label { class: "form-control",
+                        div { class: "label",
+                            span { class: "label-text", "{field.label()}" }
+                        }
+                        select {
+                            class: "select select-bordered select-sm",
+                            onchange: move |e| {
+                                match e.value().parse::<usize>() {
+                                    Ok(column) => mapping.write().insert(field, column),
+                                    Err(_) => mapping.write().remove(&field),
+                                };
+                            },
+                            option {
+                                value: "",
+                                selected: !mapping.read().contains_key(&field),
+                                if field == Field::Title { "Choose a column" } else { "Not imported" }
+                            }
+                            for (column, header) in headers.iter().enumerate() {
+                                option {
+                                    value: "{column}",
+                                    selected: mapping.read().get(&field) == Some(&column),
+                                    "{header}"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            h2 { class: "font-semibold mt-4 mb-1", "Existing counters" }
+            for choice in all::<Mode>() {
+                label { class: "label cursor-pointer justify-start items-start gap-2",
+                    input {
+                        class: "radio radio-sm mt-0.5",
+                        r#type: "radio",
+                        name: "import_mode",
+                        checked: mode() == choice,
+                        onchange: move |_| mode.set(choice),
+                    }
+                    span { class: "label-text",
+                        span { class: "font-medium", "{choice.label()}" }
+                        br {}
+                        span { class: "opacity-70", "{choice.description()}" }
+                    }
+                }
+            }
+
+            h2 { class: "font-semibold mt-4 mb-1", "Preview" }
+            div { class: "overflow-x-auto",
+                table { class: "table table-xs",
+                    thead {
+                        tr {
+                            th { "Row" }
+                            for field in all::<Field>() {
+                                th { "{field.label()}" }
+                            }
+                        }
+                    }
+                    tbody {
+                        for (index, draft) in outcome().rows.into_iter().enumerate().take(PREVIEW_ROWS) {
+                            tr {
+                                td { "{index + 1}" }
+                                match draft {
+                                    Ok(draft) => rsx! {
+                                        td { "{draft.title}" }
+                                        td {
+                                            {draft.count.map(|(kind, count)| kind.format(count))}
+                                        }
+                                        td {
+                                            {
+                                                draft
+                                                    .color
+                                                    .map(|(color, custom)| {
+                                                        custom.map_or_else(|| format!("{color:?}"), Rgb::to_hex)
+                                                    })
+                                            }
+                                        }
+                                        td { {draft.tags.map(|tags| tags.join(", "))} }
+                                    },
+                                    Err(err) => rsx! {
+                                        td { class: "text-error", colspan: 4, "{err}" }
+                                    },
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if drafts().len() > PREVIEW_ROWS {
+                p { class: "text-xs opacity-70 mt-1",
+                    "and {drafts().len() - PREVIEW_ROWS} more rows"
+                }
+            }
+
+            if !errors.is_empty() {
+                div { role: "alert", class: "alert alert-warning mt-4 flex flex-col items-start",
+                    span { "{errors.len()} rows can't be imported and will be skipped:" }
+                    ul { class: "text-sm max-h-32 overflow-y-auto w-full",
+                        for (row, err) in errors {
+                            li { "Row {row}: {err}" }
+                        }
+                    }
+                }
+            }
+
+            div { class: "modal-action",
+                button { class: "btn", onclick: move |_| table.set(None), "Cancel" }
+                button {
+                    class: "btn btn-primary",
+                    disabled: importable == 0,
+                    onclick: handle_import,
+                    "Import {importable} counters"
+                }
+            }
+        }
+        if let Some(result) = status() {
+            div { class: "toast toast-end z-10",
+                div {
+                    role: "alert",
+                    class: if result.is_ok() { "alert alert-success" } else { "alert alert-error" },
+                    span {
+                        match &result {
+                            Ok(message) => message.clone(),
+                            Err(err) => format!("Import failed: {err}"),
+                        }
+                    }
+                    button { class: "btn btn-sm btn-ghost", onclick: move |_| status.set(None), "✕" }
+                }
+            }
+        }
+    }
+}
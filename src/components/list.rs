@@ -1,14 +1,33 @@
+use std::collections::HashMap;
+
 use dioxus::prelude::*;
 use uuid::Uuid;
 
 use crate::{
+    color::Rgb,
     components::counter::TallyCounter,
-    models::{Color, Counter},
+    icon::Icon,
+    models::{Color, Counter, Group, Limits},
+    schedule::ResetSchedule,
+    value::ValueKind,
 };
 
+/// Returns `counters` with the item at `from` moved to `to`.
+fn move_item(mut counters: Vec<Counter>, from: usize, to: usize) -> Vec<Counter> {
+    let counter = counters.remove(from);
+    counters.insert(to.min(counters.len()), counter);
+    counters
+}
+
 #[component]
 pub fn CounterList(
     counters: Vec<Counter>,
+    /// Title characters matched by the filter, per counter.
+    #[props(default)]
+    highlights: HashMap<Uuid, Vec<usize>>,
+    /// Whether counters can be dragged and moved. Off while the list is sorted.
+    #[props(default = true)]
+    reorderable: bool,
     onincrement: EventHandler<Uuid>,
     ondecrement: EventHandler<Uuid>,
     ondelete: EventHandler<Uuid>,
@@ -18,24 +37,167 @@ pub fn CounterList(
     onmovebottom: EventHandler<Uuid>,
     onreorder: EventHandler<Vec<Counter>>,
     ontitlechange: EventHandler<(Uuid, String)>,
-    onvaluechange: EventHandler<(Uuid, usize)>,
+    onvaluechange: EventHandler<(Uuid, i64)>,
+    onstepchange: EventHandler<(Uuid, i64)>,
+    onlimitschange: EventHandler<(Uuid, Limits)>,
+    onkindchange: EventHandler<(Uuid, ValueKind)>,
+    onresetvaluechange: EventHandler<(Uuid, i64)>,
+    onreset: EventHandler<Uuid>,
+    onschedulechange: EventHandler<(Uuid, Option<ResetSchedule>)>,
+    ongroupchange: EventHandler<(Uuid, Option<Uuid>)>,
+    ontagschange: EventHandler<(Uuid, Vec<String>)>,
+    onnoteschange: EventHandler<(Uuid, String)>,
+    oniconchange: EventHandler<(Uuid, Option<Icon>)>,
+    onopen: EventHandler<Uuid>,
+    /// Groups counters can be moved to.
+    #[props(default)]
+    groups: Vec<Group>,
     onsetcolor: EventHandler<(Uuid, Color)>,
+    oncustomcolorchange: EventHandler<(Uuid, Rgb)>,
 ) -> Element {
+    // The counter being dragged and the index it would land on if dropped now.
+    let mut dragging = use_signal(|| None::<Uuid>);
+    let mut drop_target = use_signal(|| None::<usize>);
+
+    let mut cancel_drag = move || {
+        dragging.set(None);
+        drop_target.set(None);
+    };
+
+    // A drag held while the list is filtered, or counters are added, removed
+    // or moved, would land somewhere the user didn't aim, so it is dropped.
+    let ids = counters
+        .iter()
+        .map(|counter| counter.id)
+        .collect::<Vec<_>>();
+    use_effect(use_reactive((&ids,), move |_| cancel_drag()));
+
+    let finish_drag = use_callback({
+        let counters = counters.clone();
+        move |()| {
+            if let (Some(id), Some(to)) = (dragging(), drop_target()) {
+                // Looked up now, as the counter may be gone since it was picked up.
+                let from = counters.iter().position(|counter| counter.id == id);
+                if let Some(from) = from.filter(|from| *from != to) {
+                    onreorder(move_item(counters.clone(), from, to));
+                }
+            }
+            cancel_drag();
+        }
+    });
+
+    let last_index = counters.len().saturating_sub(1);
+
+    let dragged_index =
+        dragging().and_then(|id| counters.iter().position(|counter| counter.id == id));
+
+    let indicator_before = move |index: usize| matches!((dragged_index, drop_target()), (Some(from), Some(to)) if to == index && to < from);
+
+    let indicator_after = move |index: usize| matches!((dragged_index, drop_target()), (Some(from), Some(to)) if to == index && to > from);
+
     rsx! {
         div { class: "join join-vertical mx-auto justify-center my-7",
-            for item in counters {
-                TallyCounter {
-                    counter: item,
-                    onincrement,
-                    ondecrement,
-                    ondelete,
-                    onmovedown,
-                    onmovebottom,
-                    onmovetop,
-                    onmoveup,
-                    onvaluechange,
-                    ontitlechange,
-                    onsetcolor
+            for (index , item) in counters.into_iter().enumerate() {
+                div {
+                    key: "{item.id}",
+                    class: if dragging() == Some(item.id) { "opacity-50" } else { "" },
+                    ondragover: move |e| {
+                        e.prevent_default();
+                        if dragging().is_some() {
+                            drop_target.set(Some(index));
+                        }
+                    },
+                    ondrop: move |e| {
+                        e.prevent_default();
+                        finish_drag(());
+                    },
+                    if indicator_before(index) {
+                        div { class: "h-1 rounded bg-primary mx-14 my-0.5" }
+                    }
+                    TallyCounter {
+                        counter: item.clone(),
+                        highlight: highlights.get(&item.id).cloned().unwrap_or_default(),
+                        onincrement,
+                        ondecrement,
+                        ondelete,
+                        onmovedown,
+                        onmovebottom,
+                        onmovetop,
+                        onmoveup,
+                        onvaluechange,
+                        onstepchange,
+                        onlimitschange,
+                        onkindchange,
+                        onresetvaluechange,
+                        onreset,
+                        onschedulechange,
+                        ontitlechange,
+                        onsetcolor,
+                        oncustomcolorchange,
+                        ongroupchange,
+                        ontagschange,
+                        onnoteschange,
+                        oniconchange,
+                        onopen,
+                        groups: groups.clone(),
+                        reorderable,
+                        drag_handle: rsx! {
+                            if reorderable {
+                                button {
+                                    class: format!(
+                                        "btn btn-ghost btn-square btn-sm cursor-grab self-center {}",
+                                        if dragging() == Some(item.id) { "btn-active" } else { "" },
+                                    ),
+                                    draggable: true,
+                                    title: "Drag to reorder, or press Space and use the arrow keys",
+                                    aria_label: "Reorder {item.title}",
+                                    aria_pressed: dragging() == Some(item.id),
+                                    ondragstart: move |_| {
+                                        dragging.set(Some(item.id));
+                                        drop_target.set(Some(index));
+                                    },
+                                    ondragend: move |_| cancel_drag(),
+                                    onblur: move |_| {
+                                        if dragging() == Some(item.id) {
+                                            cancel_drag();
+                                        }
+                                    },
+                                    onkeydown: move |e| match e.key() {
+                                        Key::Character(key) if key == " " => {
+                                            e.prevent_default();
+                                            if dragging() == Some(item.id) {
+                                                finish_drag(());
+                                            } else {
+                                                dragging.set(Some(item.id));
+                                                drop_target.set(Some(index));
+                                            }
+                                        }
+                                        Key::Enter if dragging() == Some(item.id) => {
+                                            e.prevent_default();
+                                            finish_drag(());
+                                        }
+                                        Key::ArrowUp if dragging() == Some(item.id) => {
+                                            e.prevent_default();
+                                            drop_target.set(drop_target().map(|to| to.saturating_sub(1)));
+                                        }
+                                        Key::ArrowDown if dragging() == Some(item.id) => {
+                                            e.prevent_default();
+                                            drop_target.set(drop_target().map(|to| (to + 1).min(last_index)));
+                                        }
+                                        Key::Escape => cancel_drag(),
+                                        _ => {}
+                                    },
+                                    img {
+                                        class: "h-5 w-5 dark:invert",
+                                        src: asset!("public/assets/grip-vertical.svg"),
+                                    }
+                                }
+                            }
+                        },
+                    }
+                    if indicator_after(index) {
+                        div { class: "h-1 rounded bg-primary mx-14 my-0.5" }
+                    }
                 }
             }
         }
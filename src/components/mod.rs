@@ -1,4 +1,13 @@
+pub mod color_picker;
 pub mod counter;
+pub mod dashboard;
+pub mod detail;
+pub mod event_log;
+pub mod export_dialog;
+pub mod group;
+pub mod import_dialog;
 pub mod list;
 pub mod modal;
 pub mod navbar;
+pub mod schedule_editor;
+pub mod value_input;
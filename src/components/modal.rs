@@ -1,12 +1,27 @@
 use dioxus::prelude::*;
 
 #[component]
-pub fn Modal(id: String, #[props(into, default = rsx!())] children: Element) -> Element {
+pub fn Modal(
+    id: String,
+    #[props(into, default = rsx!())] children: Element,
+    /// Called when the ✕ button or Escape closes the dialog.
+    #[props(default)]
+    onclose: EventHandler<()>,
+) -> Element {
     rsx! {
-        dialog { class: "modal", id,
+        dialog {
+            class: "modal",
+            id,
+            onkeydown: move |event| {
+                if event.key() == Key::Escape {
+                    onclose.call(());
+                }
+            },
             div { class: "modal-box",
                 form { method: "dialog",
-                    button { class: "btn btn-sm btn-circle btn-ghost absolute right-2 top-2",
+                    button {
+                        class: "btn btn-sm btn-circle btn-ghost absolute right-2 top-2",
+                        onclick: move |_| onclose.call(()),
                         "✕"
                     }
                     {children}
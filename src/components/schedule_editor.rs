@@ -0,0 +1,110 @@
+use chrono::{NaiveTime, Weekday};
+use dioxus::prelude::*;
+
+use crate::schedule::{Repeat, ResetSchedule};
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[component]
+pub fn ScheduleEditor(
+    schedule: Option<ResetSchedule>,
+    onchange: EventHandler<Option<ResetSchedule>>,
+) -> Element {
+    let at = schedule.map_or(NaiveTime::MIN, |schedule| schedule.at);
+    let repeat = schedule.map(|schedule| schedule.repeat);
+
+    let update = move |repeat: Option<Repeat>, at: NaiveTime| {
+        onchange(repeat.map(|repeat| ResetSchedule::new(repeat, at)));
+    };
+
+    rsx! {
+        div { class: "flex flex-col gap-1",
+            select {
+                class: "select select-bordered select-sm",
+                onchange: move |e| {
+                    let repeat = match e.value().as_str() {
+                        "daily" => Some(Repeat::Daily),
+                        "weekly" => Some(Repeat::Weekly { weekday: 0 }),
+                        "monthly" => Some(Repeat::Monthly { day: 1 }),
+                        _ => None,
+                    };
+                    update(repeat, at);
+                },
+                option { value: "never", selected: repeat.is_none(), "Never" }
+                option {
+                    value: "daily",
+                    selected: matches!(repeat, Some(Repeat::Daily)),
+                    "Daily"
+                }
+                option {
+                    value: "weekly",
+                    selected: matches!(repeat, Some(Repeat::Weekly { .. })),
+                    "Weekly"
+                }
+                option {
+                    value: "monthly",
+                    selected: matches!(repeat, Some(Repeat::Monthly { .. })),
+                    "Monthly"
+                }
+            }
+            if let Some(repeat) = repeat {
+                div { class: "flex flex-row gap-1",
+                    match repeat {
+                        Repeat::Daily => rsx! {},
+                        Repeat::Weekly { weekday } => rsx! {
+                            select {
+                                class: "select select-bordered select-sm",
+                                onchange: move |e| {
+                                    if let Ok(weekday) = e.value().parse::<u8>() {
+                                        update(Some(Repeat::Weekly { weekday }), at);
+                                    }
+                                },
+                                for (index , day) in WEEKDAYS.iter().enumerate() {
+                                    option {
+                                        value: "{index}",
+                                        selected: usize::from(weekday) == index,
+                                        "{day}"
+                                    }
+                                }
+                            }
+                        },
+                        Repeat::Monthly { day } => rsx! {
+                            input {
+                                class: "input input-bordered input-sm w-16",
+                                r#type: "number",
+                                min: 1,
+                                max: 31,
+                                value: day.to_string(),
+                                oninput: move |e| {
+                                    if let Ok(day) = e.value().trim().parse::<u32>() {
+                                        if (1..=31).contains(&day) {
+                                            update(Some(Repeat::Monthly { day }), at);
+                                        }
+                                    }
+                                },
+                            }
+                        },
+                    }
+                    input {
+                        class: "input input-bordered input-sm",
+                        r#type: "time",
+                        value: at.format("%H:%M").to_string(),
+                        onchange: move |e| {
+                            if let Ok(at) = NaiveTime::parse_from_str(&e.value(), "%H:%M") {
+                                update(Some(repeat), at);
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use dioxus::prelude::*;
+
+use crate::value::{ParseValueError, ValueKind};
+
+/// Text input for a counter value that parses according to `kind`.
+///
+/// While the input has focus it keeps the text exactly as typed, so partial
+/// entries like `1.` or `-` are not reformatted under the cursor, and shows
+/// why the text was rejected instead of silently ignoring it. An empty field
+/// reports `None` when `optional` is set.
+#[component]
+pub fn ValueInput(
+    kind: ValueKind,
+    value: Option<i64>,
+    onchange: EventHandler<Option<i64>>,
+    #[props(default)] optional: bool,
+    #[props(into, default)] class: String,
+    #[props(into, default)] input_class: String,
+) -> Element {
+    let mut draft = use_signal(|| None::<String>);
+    let mut error = use_signal(|| None::<ParseValueError>);
+
+    let text = draft().unwrap_or_else(|| value.map(|value| kind.format(value)).unwrap_or_default());
+
+    rsx! {
+        div { class: "relative {class}",
+            input {
+                class: format!("{input_class} {}", if error().is_some() { "text-error" } else { "" }),
+                r#type: "text",
+                inputmode: if kind.places() > 0 { "decimal" } else { "numeric" },
+                aria_invalid: error().is_some(),
+                title: error().map(|err| err.to_string()).unwrap_or_default(),
+                value: text,
+                oninput: move |e| {
+                    let text = e.value();
+                    match kind.parse(&text) {
+                        Ok(value) => {
+                            error.set(None);
+                            onchange(Some(value));
+                        }
+                        Err(ParseValueError::Empty) if optional => {
+                            error.set(None);
+                            onchange(None);
+                        }
+                        Err(err) => error.set(Some(err)),
+                    }
+                    draft.set(Some(text));
+                },
+                onblur: move |_| {
+                    draft.set(None);
+                    error.set(None);
+                },
+            }
+            if let Some(err) = error() {
+                span { class: "absolute left-0 top-full z-10 text-xs text-error whitespace-nowrap",
+                    "{err}"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,388 @@
+use std::{
+    fmt, fs,
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::{
+    color::Rgb,
+    icon::Icon,
+    models::{Counter, EventKind, Group},
+    schedule::{Repeat, ResetSchedule},
+    storage::{self, Page, StorageError, Store},
+    value::ValueKind,
+};
+
+const COUNTER_COLUMNS: [&str; 19] = [
+    "id",
+    "title",
+    "count",
+    "kind",
+    "color",
+    "custom_color",
+    "step",
+    "reset_value",
+    "min",
+    "max",
+    "goal",
+    "group",
+    "tags",
+    "notes",
+    "icon",
+    "schedule",
+    "events",
+    "sessions",
+    "updated_at",
+];
+
+const EVENT_COLUMNS: [&str; 6] = [
+    "counter_id",
+    "counter_title",
+    "at",
+    "change",
+    "delta",
+    "value",
+];
+
+#[derive(Debug)]
+pub enum ExportError {
+    Io(io::Error),
+    Csv(csv::Error),
+    History(StorageError),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Csv(err) => write!(f, "CSV could not be written: {err}"),
+            Self::History(err) => write!(f, "the history could not be read: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Csv(err) => Some(err),
+            Self::History(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<csv::Error> for ExportError {
+    fn from(err: csv::Error) -> Self {
+        Self::Csv(err)
+    }
+}
+
+impl From<StorageError> for ExportError {
+    fn from(err: StorageError) -> Self {
+        Self::History(err)
+    }
+}
+
+/// The file name suggested for the event history when the counters are
+/// saved to `path`: `counters.csv` suggests `counters-events.csv`.
+pub fn events_file_name(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "counters".into(), |stem| stem.to_string_lossy());
+    format!("{stem}-events.csv")
+}
+
+/// Writes the counters to `path`, and their full event history to `events`
+/// when given. The history saved in `store` is followed by the entries the
+/// counters hold that it doesn't. Returns every file written.
+pub fn write(
+    store: &dyn Store,
+    path: &Path,
+    events: Option<&Path>,
+    counters: &[Counter],
+    groups: &[Group],
+) -> Result<Vec<PathBuf>, ExportError> {
+    fs::write(path, counters_csv(store, counters, groups)?)?;
+    let mut written = vec![path.to_path_buf()];
+
+    if let Some(events) = events {
+        events_csv(store, counters, BufWriter::new(fs::File::create(events)?))?;
+        written.push(events.to_path_buf());
+    }
+
+    Ok(written)
+}
+
+/// One row per counter with its current state. Values are formatted the way
+/// they are shown, so decimals keep their places. The history columns count
+/// what `store` has saved and what the counters hold besides.
+pub fn counters_csv(
+    store: &dyn Store,
+    counters: &[Counter],
+    groups: &[Group],
+) -> Result<Vec<u8>, ExportError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(COUNTER_COLUMNS)?;
+
+    for counter in counters {
+        let stats = store.stats(counter.id)?;
+        let kind = counter.kind;
+        let optional =
+            |value: Option<i64>| value.map(|value| kind.format(value)).unwrap_or_default();
+        let group = counter
+            .group
+            .and_then(|id| groups.iter().find(|group| group.id == id))
+            .map(|group| group.name.clone())
+            .unwrap_or_default();
+
+        writer.write_record([
+            counter.id.to_string(),
+            counter.title.clone(),
+            kind.format(counter.count),
+            kind_name(kind),
+            format!("{:?}", counter.color),
+            counter.custom_color.map(Rgb::to_hex).unwrap_or_default(),
+            kind.format(counter.step),
+            kind.format(counter.reset_value),
+            optional(counter.limits.min),
+            optional(counter.limits.max),
+            optional(counter.limits.goal),
+            group,
+            counter.tags.join(", "),
+            counter.notes.clone(),
+            counter.icon.as_ref().map(icon_name).unwrap_or_default(),
+            counter
+                .schedule
+                .as_ref()
+                .map(schedule_name)
+                .unwrap_or_default(),
+            (stats.events + counter.events.len()).to_string(),
+            (stats.sessions + counter.sessions.len()).to_string(),
+            counter.updated_at.map(timestamp).unwrap_or_default(),
+        ])?;
+    }
+
+    Ok(writer
+        .into_inner()
+        .map_err(csv::IntoInnerError::into_error)?)
+}
+
+/// Every recorded change in long format: one row per event, oldest first
+/// within each counter. The history is read from `store` and written out one
+/// counter at a time.
+pub fn events_csv(
+    store: &dyn Store,
+    counters: &[Counter],
+    out: impl io::Write,
+) -> Result<(), ExportError> {
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(EVENT_COLUMNS)?;
+
+    for counter in counters {
+        let saved = store.events(counter.id, storage::all_time(), Page::ALL)?;
+        for event in saved.iter().rev().chain(&counter.events) {
+            writer.write_record([
+                counter.id.to_string(),
+                counter.title.clone(),
+                timestamp(event.at),
+                event_name(event.kind).to_string(),
+                counter.kind.format(event.delta),
+                counter.kind.format(event.value),
+            ])?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn kind_name(kind: ValueKind) -> String {
+    match kind {
+        ValueKind::Unsigned => String::from("unsigned"),
+        ValueKind::Signed => String::from("signed"),
+        ValueKind::Decimal { places } => format!("decimal({places})"),
+    }
+}
+
+const fn event_name(kind: EventKind) -> &'static str {
+    match kind {
+        EventKind::Increment => "increment",
+        EventKind::Decrement => "decrement",
+        EventKind::Set => "set",
+        EventKind::Reset => "reset",
+        EventKind::Undo => "undo",
+        EventKind::Redo => "redo",
+    }
+}
+
+fn icon_name(icon: &Icon) -> String {
+    match icon {
+        Icon::Bundled(bundled) => bundled.label().to_string(),
+        Icon::Emoji(emoji) => emoji.clone(),
+    }
+}
+
+fn schedule_name(schedule: &ResetSchedule) -> String {
+    let at = schedule.at.format("%H:%M");
+    match schedule.repeat {
+        Repeat::Daily => format!("daily at {at}"),
+        Repeat::Weekly { weekday } => format!("weekly on {} at {at}", Repeat::weekday(weekday)),
+        Repeat::Monthly { day } => format!("monthly on day {day} at {at}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        models::CounterEvent,
+        storage::{json::JsonStore, Document, Unsaved},
+    };
+
+    const TITLE: &str = "Milk, \"whole\"\nfresh ☕";
+    const NOTES: &str = "Crème brûlée, 2×\r\nline \"two\"";
+
+    fn counter() -> Counter {
+        Counter {
+            title: TITLE.to_string(),
+            notes: NOTES.to_string(),
+            tags: vec![String::from("dairy"), String::from("frühstück")],
+            count: 3,
+            events: vec![CounterEvent {
+                at: Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap(),
+                kind: EventKind::Increment,
+                delta: 1,
+                value: 3,
+            }],
+            ..Counter::default()
+        }
+    }
+
+    /// A store with nothing saved, in a file of its own under the system's
+    /// temporary directory.
+    fn store() -> JsonStore {
+        JsonStore::new(std::env::temp_dir().join(format!("tallyfy-export-{}.json", Uuid::new_v4())))
+    }
+
+    fn rows(csv: &[u8]) -> Vec<csv::StringRecord> {
+        csv::Reader::from_reader(csv)
+            .records()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn quotes_commas_quotes_and_newlines() {
+        let csv = counters_csv(&store(), &[counter()], &[]).unwrap();
+        let text = String::from_utf8(csv.clone()).unwrap();
+
+        assert!(text.contains("\"Milk, \"\"whole\"\"\nfresh ☕\""), "{text}");
+        assert!(
+            text.contains("\"Crème brûlée, 2×\r\nline \"\"two\"\"\""),
+            "{text}"
+        );
+
+        let rows = rows(&csv);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(&rows[0][1], TITLE);
+        assert_eq!(&rows[0][12], "dairy, frühstück");
+        assert_eq!(&rows[0][13], NOTES);
+    }
+
+    #[test]
+    fn events_keep_the_quoted_title() {
+        let counter = counter();
+        let mut csv = Vec::new();
+        events_csv(&store(), std::slice::from_ref(&counter), &mut csv).unwrap();
+        let rows = rows(&csv);
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(&rows[0][0], counter.id.to_string());
+        assert_eq!(&rows[0][1], TITLE);
+        assert_eq!(&rows[0][2], "2026-03-01T12:00:00Z");
+        assert_eq!(&rows[0][3], "increment");
+    }
+
+    #[test]
+    fn names_groups_and_leaves_missing_ones_empty() {
+        let group = Group {
+            name: String::from("Kitchen, upstairs"),
+            ..Group::default()
+        };
+        let grouped = Counter {
+            group: Some(group.id),
+            ..counter()
+        };
+        let orphan = Counter {
+            group: Some(Uuid::new_v4()),
+            ..counter()
+        };
+
+        let rows = rows(&counters_csv(&store(), &[grouped, orphan], &[group]).unwrap());
+        assert_eq!(&rows[0][11], "Kitchen, upstairs");
+        assert_eq!(&rows[1][11], "");
+    }
+
+    #[test]
+    fn saved_history_comes_before_the_entries_the_counter_holds() {
+        let counter = counter();
+        let saved = CounterEvent {
+            at: Utc.with_ymd_and_hms(2026, 2, 1, 12, 0, 0).unwrap(),
+            kind: EventKind::Set,
+            delta: 2,
+            value: 2,
+        };
+        let store = store();
+        let document = Document {
+            counters: vec![Counter {
+                events: Vec::new(),
+                ..counter.clone()
+            }],
+            ..Document::default()
+        };
+        let unsaved = Unsaved {
+            counter: counter.id,
+            kind: counter.kind,
+            events: vec![saved],
+            sessions: Vec::new(),
+        };
+        store.save(&document, &[unsaved]).unwrap();
+
+        let counters = std::slice::from_ref(&counter);
+        let summary = rows(&counters_csv(&store, counters, &[]).unwrap());
+        assert_eq!(&summary[0][16], "2");
+        assert_eq!(&summary[0][17], "0");
+
+        let mut csv = Vec::new();
+        events_csv(&store, counters, &mut csv).unwrap();
+        let changes = rows(&csv)
+            .iter()
+            .map(|row| row[3].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(changes, ["set", "increment"]);
+    }
+
+    #[test]
+    fn suggests_an_events_file_beside_the_counters() {
+        assert_eq!(
+            events_file_name(Path::new("/tmp/counters.csv")),
+            "counters-events.csv"
+        );
+        assert_eq!(events_file_name(Path::new("")), "counters-events.csv");
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use uuid::Uuid;
+
+use crate::{
+    fuzzy::Match,
+    models::{Color, Counter},
+    query::Query,
+};
+
+/// Criteria from the filter panel. Every active criterion must match, and a
+/// filter with no active criteria matches every counter.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Filter {
+    /// Counters with any of these colors match. Empty means any color.
+    pub colors: HashSet<Color>,
+    /// Counters carrying all of these tags match. Empty means any tags.
+    pub tags: Vec<String>,
+    /// Parsed contents of the filter text box. Text is matched ignoring case
+    /// and diacritics. An empty query matches any counter.
+    pub query: Query,
+}
+
+impl Filter {
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty() && self.tags.is_empty() && self.query.is_empty()
+    }
+
+    pub fn evaluate(&self, counter: &Counter) -> Option<Match> {
+        if !self.colors.is_empty() && !self.colors.contains(&counter.color) {
+            return None;
+        }
+        if !self.tags.iter().all(|tag| counter.has_tag(tag)) {
+            return None;
+        }
+
+        self.query.evaluate(counter)
+    }
+
+    /// Keeps the matching counters. When the query contains fuzzy terms the
+    /// best matches come first; otherwise the existing order is kept.
+    pub fn apply(&self, counters: Vec<Counter>) -> Vec<Counter> {
+        let mut matched = counters
+            .into_iter()
+            .filter_map(|counter| self.evaluate(&counter).map(|found| (counter, found.score)))
+            .collect::<Vec<_>>();
+
+        if self.query.has_fuzzy_terms() {
+            matched.sort_by_key(|(_, score)| Reverse(*score));
+        }
+
+        matched.into_iter().map(|(counter, _)| counter).collect()
+    }
+
+    /// Title characters to highlight for each matching counter.
+    pub fn highlights(&self, counters: &[Counter]) -> HashMap<Uuid, Vec<usize>> {
+        counters
+            .iter()
+            .filter_map(|counter| {
+                self.evaluate(counter)
+                    .map(|found| (counter.id, found.positions))
+            })
+            .filter(|(_, positions)| !positions.is_empty())
+            .collect()
+    }
+}
+
+/// Folds `text` for comparison: lowercased, with accents and other combining
+/// marks removed, so "Café" and "cafe" compare equal.
+pub fn normalize(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
@@ -0,0 +1,268 @@
+use crate::filter::normalize;
+
+const MATCH: i64 = 16;
+const CONSECUTIVE: i64 = 24;
+const WORD_START: i64 = 20;
+const GAP: i64 = 2;
+const MAX_LEADING_PENALTY: i64 = 10;
+/// Taken off for each typo.
+const TYPO: i64 = 2 * (MATCH + CONSECUTIVE + WORD_START);
+
+/// Result of a fuzzy match: higher scores are better matches, and
+/// `positions` are the matched character indices in the original text.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub score: i64,
+    pub positions: Vec<usize>,
+}
+
+/// Matches `pattern` against `text`, ignoring case and diacritics.
+///
+/// `pattern` is first matched as a subsequence: every possible starting point
+/// is tried and the alignment that rewards consecutive characters and word
+/// starts most is kept. Failing that, it may match the start of a word with
+/// a typo or two, each of which costs a good deal of score.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<Match> {
+    let pattern = normalize(pattern).chars().collect::<Vec<_>>();
+    let first = *pattern.first()?;
+
+    // Each folded character remembers which original character it came from.
+    let folded = text
+        .chars()
+        .enumerate()
+        .flat_map(|(index, c)| {
+            normalize(&c.to_string())
+                .chars()
+                .map(move |folded| (index, folded))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    folded
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, c))| *c == first)
+        .filter_map(|(start, _)| align(&pattern, &folded, start))
+        .max_by_key(|found| found.score)
+        .or_else(|| with_typos(&pattern, &folded))
+}
+
+/// How many typos a pattern of `len` characters may contain. Short patterns
+/// allow none, or nearly anything would match them.
+const fn allowed_typos(len: usize) -> usize {
+    match len {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Matches `pattern` against the start of each word in `folded`, allowing
+/// [`allowed_typos`] edits. The closest word start wins.
+fn with_typos(pattern: &[char], folded: &[(usize, char)]) -> Option<Match> {
+    let allowed = allowed_typos(pattern.len());
+    if allowed == 0 {
+        return None;
+    }
+
+    let chars = folded.iter().map(|(_, c)| *c).collect::<Vec<_>>();
+    let words = (0..chars.len())
+        .filter(|&at| chars[at].is_alphanumeric() && (at == 0 || !chars[at - 1].is_alphanumeric()))
+        .map(|start| {
+            let len = chars[start..]
+                .iter()
+                .position(|c| !c.is_alphanumeric())
+                .unwrap_or(chars.len() - start);
+            (start, len)
+        });
+
+    let (start, len, typos) = words
+        .flat_map(|(start, word_len)| {
+            let shortest = pattern.len().saturating_sub(allowed).max(1);
+            let longest = (pattern.len() + allowed).min(word_len);
+            (shortest..=longest).map(move |len| (start, len))
+        })
+        .map(|(start, len)| {
+            (
+                start,
+                len,
+                edit_distance(pattern, &chars[start..start + len]),
+            )
+        })
+        .filter(|(_, _, typos)| *typos <= allowed)
+        .min_by_key(|(start, _, typos)| (*typos, *start))?;
+
+    let mut positions = folded[start..start + len]
+        .iter()
+        .map(|(index, _)| *index)
+        .collect::<Vec<_>>();
+    positions.dedup();
+
+    let matched = i64::try_from(pattern.len() - typos).unwrap_or(0);
+    let typos = i64::try_from(typos).unwrap_or(i64::MAX / TYPO);
+    Some(Match {
+        score: matched * MATCH - typos * TYPO,
+        positions,
+    })
+}
+
+/// Edits needed to turn `a` into `b`: insertions, deletions, substitutions
+/// and swaps of neighbouring characters.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1)
+                .min(row[j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+fn align(pattern: &[char], folded: &[(usize, char)], start: usize) -> Option<Match> {
+    let is_word_start = |at: usize| {
+        at == 0
+            || folded
+                .get(at - 1)
+                .is_some_and(|(_, c)| !c.is_alphanumeric())
+    };
+
+    let mut score = -i64::try_from(start)
+        .unwrap_or(MAX_LEADING_PENALTY)
+        .min(MAX_LEADING_PENALTY);
+    let mut positions = Vec::with_capacity(pattern.len());
+    let mut previous: Option<usize> = None;
+    let mut at = start;
+
+    for wanted in pattern {
+        let found = at + folded[at..].iter().position(|(_, c)| c == wanted)?;
+
+        score += MATCH;
+        match previous {
+            Some(previous) if found == previous + 1 => score += CONSECUTIVE,
+            Some(previous) => {
+                score -= GAP * i64::try_from(found - previous - 1).unwrap_or(i64::MAX / GAP);
+            }
+            None => {}
+        }
+        if is_word_start(found) {
+            score += WORD_START;
+        }
+
+        let (index, _) = folded[found];
+        if positions.last() != Some(&index) {
+            positions.push(index);
+        }
+        previous = Some(found);
+        at = found + 1;
+    }
+
+    Some(Match { score, positions })
+}
+
+/// Splits `text` into runs of characters, flagging the runs whose indices
+/// are in `positions`.
+pub fn segments(text: &str, positions: &[usize]) -> Vec<(String, bool)> {
+    let mut segments: Vec<(String, bool)> = Vec::new();
+    for (index, c) in text.chars().enumerate() {
+        let highlighted = positions.contains(&index);
+        match segments.last_mut() {
+            Some((segment, flag)) if *flag == highlighted => segment.push(c),
+            _ => segments.push((c.to_string(), highlighted)),
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(pattern: &str, text: &str) -> i64 {
+        fuzzy_match(pattern, text).unwrap().score
+    }
+
+    #[test]
+    fn consecutive_characters_rank_first() {
+        assert!(score("milk", "Milk") > score("milk", "Mixed lake"));
+    }
+
+    #[test]
+    fn word_starts_rank_above_the_middle_of_words() {
+        assert!(score("box", "Boxes") > score("box", "Inbox"));
+        assert!(score("mb", "Milk bottles") > score("mb", "Gumbo"));
+    }
+
+    #[test]
+    fn earlier_matches_rank_higher() {
+        assert!(score("milk", "Milk stock") > score("milk", "Stock of milk"));
+    }
+
+    #[test]
+    fn finds_the_best_alignment() {
+        let found = fuzzy_match("bot", "a bit of bottles").unwrap();
+        assert_eq!(found.positions, [9, 10, 11]);
+    }
+
+    #[test]
+    fn ignores_case_and_accents() {
+        let found = fuzzy_match("cafe", "CAFÉ au lait").unwrap();
+        assert_eq!(found.positions, [0, 1, 2, 3]);
+        assert_eq!(score("CAFÉ", "cafe"), score("cafe", "cafe"));
+    }
+
+    #[test]
+    fn tolerates_typos_below_exact_matches() {
+        let typo = fuzzy_match("mlik", "Milk").unwrap();
+        assert_eq!(typo.positions, [0, 1, 2, 3]);
+        assert!(typo.score < score("milk", "Milk"));
+        assert!(typo.score < score("mlk", "Milk"));
+
+        let found = fuzzy_match("bittles", "Milk bottles").unwrap();
+        assert_eq!(found.positions, [5, 6, 7, 8, 9, 10, 11]);
+        assert!(fuzzy_match("bottels", "Milk bottles").is_some());
+        assert!(score("warehuose", "Warehouse") > score("wrehuose", "Warehouse"));
+    }
+
+    #[test]
+    fn limits_typos_by_length() {
+        assert!(fuzzy_match("mix", "Milk").is_none());
+        assert!(fuzzy_match("mxik", "Milk").is_none());
+        assert!(fuzzy_match("bxttlxs", "Milk bottles").is_none());
+        assert!(fuzzy_match("wearhuose", "Warehouse").is_none());
+        assert!(fuzzy_match("", "Milk").is_none());
+    }
+
+    #[test]
+    fn counts_edits() {
+        let distance = |a: &str, b: &str| {
+            edit_distance(
+                &a.chars().collect::<Vec<_>>(),
+                &b.chars().collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("milk", "mlik"), 1);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("abc", "abc"), 0);
+    }
+
+    #[test]
+    fn splits_highlighted_segments() {
+        assert_eq!(
+            segments("Milk", &[0, 2, 3]),
+            [
+                (String::from("M"), true),
+                (String::from("i"), false),
+                (String::from("lk"), true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+const MAX_STEPS: usize = 100;
+
+/// Edits arriving this soon after a matching one are folded into the same undo step.
+const COALESCE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Identifies edits that should merge into a single undo step when repeated quickly,
+/// such as each keystroke typed into a counter's title.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coalesce {
+    Title(Uuid),
+    Value(Uuid),
+    Step(Uuid),
+    Limits(Uuid),
+    ResetValue(Uuid),
+    Schedule(Uuid),
+    Color(Uuid),
+    Notes(Uuid),
+}
+
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    last_edit: Option<(Coalesce, Instant)>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last_edit: None,
+        }
+    }
+}
+
+impl<T> History<T> {
+    /// Records `previous` as the state to return to, unless the edit continues
+    /// the one recorded just before it.
+    pub fn record(&mut self, previous: T, coalesce: Option<Coalesce>) {
+        self.record_at(previous, coalesce, Instant::now());
+    }
+
+    fn record_at(&mut self, previous: T, coalesce: Option<Coalesce>, now: Instant) {
+        let continues = matches!(
+            (coalesce, self.last_edit),
+            (Some(key), Some((last, at))) if key == last && now.duration_since(at) < COALESCE_WINDOW
+        );
+
+        self.last_edit = coalesce.map(|key| (key, now));
+        self.redo.clear();
+
+        if continues {
+            return;
+        }
+
+        self.undo.push(previous);
+        if self.undo.len() > MAX_STEPS {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Returns the state before the last recorded edit, keeping `current` for redo.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        self.last_edit = None;
+        Some(previous)
+    }
+
+    /// Returns the state most recently undone, keeping `current` for undo.
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        self.last_edit = None;
+        Some(next)
+    }
+
+    /// Applies `change` to every state kept for undo and redo, for changes
+    /// that are not themselves undoable.
+    pub fn rewrite(&mut self, change: impl FnMut(&mut T)) {
+        self.undo.iter_mut().chain(&mut self.redo).for_each(change);
+    }
+
+    /// The state [`History::undo`] would return next.
+    pub fn next_undo(&self) -> Option<&T> {
+        self.undo.last()
+    }
+
+    /// The state [`History::redo`] would return next.
+    pub fn next_redo(&self) -> Option<&T> {
+        self.redo.last()
+    }
+
+    pub const fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub const fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded(steps: usize) -> History<usize> {
+        let mut history = History::default();
+        for step in 0..steps {
+            history.record(step, None);
+        }
+        history
+    }
+
+    #[test]
+    fn undo_and_redo_walk_back_and_forth() {
+        let mut history = recorded(2);
+
+        assert_eq!(history.undo(2), Some(1));
+        assert_eq!(history.undo(1), Some(0));
+        assert_eq!(history.undo(0), None);
+        assert!(!history.can_undo());
+        assert_eq!(history.redo(0), Some(1));
+        assert_eq!(history.redo(1), Some(2));
+        assert_eq!(history.redo(2), None);
+        assert!(history.can_undo());
+    }
+
+    #[test]
+    fn recording_clears_redo() {
+        let mut history = recorded(2);
+        assert_eq!(history.undo(2), Some(1));
+        assert!(history.can_redo());
+
+        history.record(1, None);
+        assert!(!history.can_redo());
+        assert_eq!(history.next_undo(), Some(&1));
+    }
+
+    #[test]
+    fn repeated_edits_coalesce_within_the_window() {
+        let id = Uuid::new_v4();
+        let start = Instant::now();
+        let mut history = History::default();
+
+        history.record_at(0, Some(Coalesce::Title(id)), start);
+        history.record_at(1, Some(Coalesce::Title(id)), start + COALESCE_WINDOW / 2);
+        // Each edit extends the window from the one before it.
+        history.record_at(2, Some(Coalesce::Title(id)), start + COALESCE_WINDOW);
+        assert_eq!(history.undo(3), Some(0));
+        assert!(!history.can_undo());
+
+        let mut history = History::default();
+        history.record_at(0, Some(Coalesce::Title(id)), start);
+        history.record_at(1, Some(Coalesce::Title(id)), start + COALESCE_WINDOW);
+        history.record_at(2, Some(Coalesce::Step(id)), start + COALESCE_WINDOW);
+        history.record_at(
+            3,
+            Some(Coalesce::Title(Uuid::new_v4())),
+            start + COALESCE_WINDOW,
+        );
+        history.record_at(4, None, start + COALESCE_WINDOW);
+        history.record_at(5, None, start + COALESCE_WINDOW);
+        assert_eq!(history.undo.len(), 6);
+    }
+
+    #[test]
+    fn undoing_ends_the_coalesced_edit() {
+        let id = Uuid::new_v4();
+        let start = Instant::now();
+        let mut history = History::default();
+
+        history.record_at(0, Some(Coalesce::Value(id)), start);
+        assert_eq!(history.undo(1), Some(0));
+        assert_eq!(history.redo(0), Some(1));
+        history.record_at(1, Some(Coalesce::Value(id)), start);
+
+        assert_eq!(history.undo(2), Some(1));
+    }
+
+    #[test]
+    fn the_oldest_steps_are_dropped_past_the_limit() {
+        let history = recorded(MAX_STEPS + 5);
+
+        assert_eq!(history.undo.len(), MAX_STEPS);
+        assert_eq!(history.undo.first(), Some(&5));
+        assert_eq!(history.next_undo(), Some(&(MAX_STEPS + 4)));
+    }
+
+    #[test]
+    fn rewrite_changes_undo_and_redo_steps() {
+        let mut history = recorded(3);
+        assert_eq!(history.undo(3), Some(2));
+
+        history.rewrite(|step| *step *= 10);
+
+        assert_eq!(history.undo.as_slice(), [0, 10]);
+        assert_eq!(history.redo.as_slice(), [30]);
+    }
+}
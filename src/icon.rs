@@ -0,0 +1,80 @@
+use dioxus::prelude::*;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+
+/// Longest emoji accepted, in chars. Sequences joined with zero-width
+/// joiners, such as families or flags, take several chars each.
+const MAX_EMOJI_CHARS: usize = 16;
+
+/// The symbols bundled in `public/assets/icons`.
+// The `unsafe` the lint sees comes from the `asset!` expansion in `asset()`.
+#[allow(clippy::unsafe_derive_deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Sequence, Serialize, Deserialize,
+)]
+pub enum BundledIcon {
+    Star,
+    Heart,
+    Package,
+    Coffee,
+    Droplet,
+    Flag,
+    Bolt,
+    Book,
+    User,
+    Bug,
+}
+
+/// Shown at the start of a counter's row.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Icon {
+    Bundled(BundledIcon),
+    Emoji(String),
+}
+
+impl BundledIcon {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Star => "Star",
+            Self::Heart => "Heart",
+            Self::Package => "Package",
+            Self::Coffee => "Coffee",
+            Self::Droplet => "Droplet",
+            Self::Flag => "Flag",
+            Self::Bolt => "Bolt",
+            Self::Book => "Book",
+            Self::User => "Person",
+            Self::Bug => "Bug",
+        }
+    }
+
+    pub fn asset(self) -> Asset {
+        match self {
+            Self::Star => asset!("public/assets/icons/star.svg"),
+            Self::Heart => asset!("public/assets/icons/heart.svg"),
+            Self::Package => asset!("public/assets/icons/package.svg"),
+            Self::Coffee => asset!("public/assets/icons/coffee.svg"),
+            Self::Droplet => asset!("public/assets/icons/droplet.svg"),
+            Self::Flag => asset!("public/assets/icons/flag.svg"),
+            Self::Bolt => asset!("public/assets/icons/bolt.svg"),
+            Self::Book => asset!("public/assets/icons/book.svg"),
+            Self::User => asset!("public/assets/icons/user.svg"),
+            Self::Bug => asset!("public/assets/icons/bug.svg"),
+        }
+    }
+}
+
+impl Icon {
+    /// An emoji icon from typed text. Plain words and numbers are rejected so
+    /// the icon can't be mistaken for part of the title.
+    pub fn emoji(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let valid = !text.is_empty()
+            && text.chars().count() <= MAX_EMOJI_CHARS
+            && !text
+                .chars()
+                .any(|c| c.is_whitespace() || c.is_ascii_alphanumeric());
+
+        valid.then(|| Self::Emoji(text.to_string()))
+    }
+}
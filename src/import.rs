@@ -0,0 +1,734 @@
+use std::{collections::HashMap, fmt};
+
+use enum_iterator::{all, Sequence};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    color::{ParseColorError, Rgb},
+    filter::normalize,
+    models::{Color, Counter, EventKind},
+    value::{ParseValueError, ValueKind, MAX_DECIMAL_PLACES},
+};
+
+/// Rows shown in the preview before importing.
+pub const PREVIEW_ROWS: usize = 10;
+
+#[derive(Debug)]
+pub enum ImportError {
+    Csv(csv::Error),
+    Json(serde_json::Error),
+    /// JSON that is neither a list of records nor a saved document.
+    NotRecords,
+    Empty,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Csv(err) => write!(f, "the CSV could not be read: {err}"),
+            Self::Json(err) => write!(f, "the JSON could not be read: {err}"),
+            Self::NotRecords => write!(f, "the JSON must be a list of objects"),
+            Self::Empty => write!(f, "the file has no rows"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Csv(err) => Some(err),
+            Self::Json(err) => Some(err),
+            Self::NotRecords | Self::Empty => None,
+        }
+    }
+}
+
+/// The counter fields a column can be imported into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Sequence)]
+pub enum Field {
+    Title,
+    Count,
+    Color,
+    Tags,
+}
+
+impl Field {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Title => "Title",
+            Self::Count => "Count",
+            Self::Color => "Color",
+            Self::Tags => "Tags",
+        }
+    }
+
+    /// Header names recognised for this field, normalized.
+    const fn aliases(self) -> &'static [&'static str] {
+        match self {
+            Self::Title => &["title", "name", "counter", "counter_title"],
+            Self::Count => &["count", "value", "total", "tally"],
+            Self::Color => &["color", "colour", "custom_color"],
+            Self::Tags => &["tags", "tag", "labels"],
+        }
+    }
+}
+
+/// How imported rows combine with the existing counters.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Sequence)]
+pub enum Mode {
+    /// Rows update the counter with the same title, or are added if none has it.
+    #[default]
+    Merge,
+    /// Every existing counter is removed first.
+    Replace,
+    /// Every row becomes a new counter.
+    Append,
+}
+
+impl Mode {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Merge => "Merge by title",
+            Self::Replace => "Replace all",
+            Self::Append => "Append",
+        }
+    }
+
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Merge => "Counters with the same title are updated, the rest are added.",
+            Self::Replace => "All current counters are removed and replaced by the imported ones.",
+            Self::Append => "Every row is added as a new counter, even if the title exists.",
+        }
+    }
+}
+
+/// Imported data before it is mapped onto counters: a header and rows of text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// The kind of each row's count when the file says it, as a saved
+    /// document does. Empty when the kind is read from how counts are written.
+    pub kinds: Vec<ValueKind>,
+}
+
+/// Which column each field is read from.
+pub type Mapping = HashMap<Field, usize>;
+
+/// A row that was read successfully. Fields whose column is not mapped are
+/// `None` and leave merged counters untouched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Draft {
+    pub title: String,
+    pub count: Option<(ValueKind, i64)>,
+    pub color: Option<(Color, Option<Rgb>)>,
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowError {
+    MissingTitle,
+    Count(String, ParseValueError),
+    Color(String, ParseColorError),
+    /// The count has more decimal places than the merged counter keeps.
+    Precision(String, ValueKind),
+    /// The count is outside the merged counter's limits.
+    OutOfRange(String),
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingTitle => write!(f, "the title is empty"),
+            Self::Count(text, err) => write!(f, "count \"{text}\": {err}"),
+            Self::Color(text, err) => write!(f, "color \"{text}\": {err}"),
+            Self::Precision(text, kind) => write!(
+                f,
+                "count \"{text}\": the counter with this title keeps {} decimal places",
+                kind.places()
+            ),
+            Self::OutOfRange(text) => write!(
+                f,
+                "count \"{text}\": outside the limits of the counter with this title"
+            ),
+        }
+    }
+}
+
+impl Table {
+    /// Reads CSV or JSON. JSON is recognised by its first character, so the
+    /// file extension does not matter.
+    pub fn parse(contents: &str) -> Result<Self, ImportError> {
+        let contents = contents.trim_start_matches('\u{feff}');
+        let table = if contents.trim_start().starts_with(['[', '{']) {
+            Self::from_json(contents)?
+        } else {
+            Self::from_csv(contents)?
+        };
+
+        if table.rows.is_empty() {
+            return Err(ImportError::Empty);
+        }
+        Ok(table)
+    }
+
+    fn from_csv(contents: &str) -> Result<Self, ImportError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(contents.as_bytes());
+
+        let headers = reader
+            .headers()
+            .map_err(ImportError::Csv)?
+            .iter()
+            .map(str::to_string)
+            .collect();
+        let rows = reader
+            .records()
+            .map(|record| {
+                record
+                    .map(|record| record.iter().map(str::to_string).collect())
+                    .map_err(ImportError::Csv)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            headers,
+            rows,
+            kinds: Vec::new(),
+        })
+    }
+
+    /// Accepts a list of objects, or a saved document with a `counters` list.
+    fn from_json(contents: &str) -> Result<Self, ImportError> {
+        let value = serde_json::from_str::<Value>(contents).map_err(ImportError::Json)?;
+        let records = match value {
+            Value::Array(records) => records,
+            Value::Object(mut document) => match document.remove("counters") {
+                Some(records @ Value::Array(_)) => {
+                    return serde_json::from_value(records)
+                        .map(Self::from_saved)
+                        .map_err(ImportError::Json)
+                }
+                _ => return Err(ImportError::NotRecords),
+            },
+            _ => return Err(ImportError::NotRecords),
+        };
+
+        let mut headers = Vec::<String>::new();
+        for record in &records {
+            let Value::Object(record) = record else {
+                return Err(ImportError::NotRecords);
+            };
+            for key in record.keys() {
+                if !headers.contains(key) {
+                    headers.push(key.clone());
+                }
+            }
+        }
+
+        let rows = records
+            .iter()
+            .map(|record| {
+                headers
+                    .iter()
+                    .map(|header| record.get(header).map(cell).unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self {
+            headers,
+            rows,
+            kinds: Vec::new(),
+        })
+    }
+
+    /// Counters from a saved document. Their counts are stored raw, so they are
+    /// written out in their own kind and read back with it.
+    fn from_saved(counters: Vec<SavedCounter>) -> Self {
+        let headers = ["title", "count", "color", "tags"].map(String::from).into();
+        let (rows, kinds) = counters
+            .into_iter()
+            .map(|counter| {
+                let color = counter
+                    .custom_color
+                    .map_or_else(|| format!("{:?}", counter.color), Rgb::to_hex);
+                let row = vec![
+                    counter.title,
+                    counter.kind.format(counter.count),
+                    color,
+                    counter.tags.join(", "),
+                ];
+                (row, counter.kind)
+            })
+            .unzip();
+
+        Self {
+            headers,
+            rows,
+            kinds,
+        }
+    }
+
+    /// Maps each field to the first column whose header names it.
+    pub fn guess_mapping(&self) -> Mapping {
+        all::<Field>()
+            .filter_map(|field| {
+                self.headers
+                    .iter()
+                    .position(|header| {
+                        let header = normalize(header.trim()).replace([' ', '-'], "_");
+                        field.aliases().contains(&header.as_str())
+                    })
+                    .map(|column| (field, column))
+            })
+            .collect()
+    }
+
+    /// Reads every row with `mapping`. The result has one entry per row, in order.
+    pub fn drafts(&self, mapping: &Mapping) -> Vec<Result<Draft, RowError>> {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(index, row)| draft(row, mapping, self.kinds.get(index).copied()))
+            .collect()
+    }
+}
+
+/// The fields of a counter in a saved document that can be imported.
+#[derive(Debug, Deserialize)]
+struct SavedCounter {
+    title: String,
+    #[serde(default)]
+    count: i64,
+    #[serde(default)]
+    kind: ValueKind,
+    #[serde(default)]
+    color: Color,
+    #[serde(default)]
+    custom_color: Option<Rgb>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Text for a JSON value: strings as they are, lists joined with commas.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(cell).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+fn draft(row: &[String], mapping: &Mapping, kind: Option<ValueKind>) -> Result<Draft, RowError> {
+    let column = |field: Field| {
+        mapping
+            .get(&field)
+            .map(|&column| row.get(column).map_or("", |text| text.trim()))
+    };
+
+    let title = column(Field::Title).unwrap_or_default();
+    if title.is_empty() {
+        return Err(RowError::MissingTitle);
+    }
+
+    Ok(Draft {
+        title: title.to_string(),
+        count: column(Field::Count)
+            .map(|text| parse_count(text, kind))
+            .transpose()?,
+        color: column(Field::Color).map(parse_color).transpose()?,
+        tags: column(Field::Tags).map(parse_tags),
+    })
+}
+
+/// Reads a count in `kind`, or if that is not known chooses the kind from
+/// how it is written: decimals keep their places and negative numbers make
+/// the counter signed.
+fn parse_count(text: &str, kind: Option<ValueKind>) -> Result<(ValueKind, i64), RowError> {
+    if text.is_empty() {
+        return Ok((kind.unwrap_or_default(), 0));
+    }
+
+    let kind = kind.unwrap_or_else(|| guess_kind(text));
+    kind.parse(text)
+        .map(|raw| (kind, raw))
+        .map_err(|err| RowError::Count(text.to_string(), err))
+}
+
+fn guess_kind(text: &str) -> ValueKind {
+    let places = text
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len());
+    if places > 0 {
+        ValueKind::Decimal {
+            places: u8::try_from(places)
+                .unwrap_or(u8::MAX)
+                .min(MAX_DECIMAL_PLACES),
+        }
+    } else if text.starts_with('-') {
+        ValueKind::Signed
+    } else {
+        ValueKind::Unsigned
+    }
+}
+
+/// A named color, or any hex or HSL value as a custom color.
+fn parse_color(text: &str) -> Result<(Color, Option<Rgb>), RowError> {
+    if text.is_empty() {
+        return Ok((Color::default(), None));
+    }
+    if let Some(color) =
+        all::<Color>().find(|color| format!("{color:?}").eq_ignore_ascii_case(text))
+    {
+        return Ok((color, None));
+    }
+
+    Rgb::parse(text)
+        .map(|rgb| (rgb.nearest_preset(), Some(rgb)))
+        .map_err(|err| RowError::Color(text.to_string(), err))
+}
+
+/// Tags separated by commas or semicolons, without blanks or duplicates.
+fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::<String>::new();
+    for tag in text.split([',', ';']).map(str::trim) {
+        if !tag.is_empty() && !tags.iter().any(|own| normalize(own) == normalize(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// The result of importing rows into the current counters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub counters: Vec<Counter>,
+    /// One entry per row, in order. Rows that could not be imported as
+    /// written are errors and left out of `counters`.
+    pub rows: Vec<Result<Draft, RowError>>,
+}
+
+/// Imports `drafts` into `counters`. Rows that were read with errors are
+/// skipped, as are rows whose count the counter they merge into can't hold.
+pub fn apply(counters: Vec<Counter>, drafts: Vec<Result<Draft, RowError>>, mode: Mode) -> Outcome {
+    let mut counters = match mode {
+        Mode::Replace => Vec::new(),
+        Mode::Merge | Mode::Append => counters,
+    };
+
+    let rows = drafts
+        .into_iter()
+        .map(|draft| {
+            let draft = draft?;
+            let existing = match mode {
+                Mode::Merge => counters
+                    .iter()
+                    .position(|counter| normalize(&counter.title) == normalize(&draft.title)),
+                Mode::Replace | Mode::Append => None,
+            };
+
+            match existing {
+                Some(index) => counters[index] = merge(&counters[index], &draft)?,
+                None => counters.push(create(draft.clone())),
+            }
+            Ok(draft)
+        })
+        .collect();
+
+    Outcome { counters, rows }
+}
+
+fn create(draft: Draft) -> Counter {
+    let (kind, count) = draft.count.unwrap_or_default();
+    let (color, custom_color) = draft.color.unwrap_or_default();
+    Counter {
+        title: draft.title,
+        kind,
+        color,
+        custom_color,
+        tags: draft.tags.unwrap_or_default(),
+        ..Counter::default()
+    }
+    .with_count(count, EventKind::Set)
+}
+
+/// Updates only the fields the import provides. Tags are added to the
+/// counter's own rather than replacing them. The count must fit the counter
+/// as written, without dropping digits or being clamped.
+fn merge(counter: &Counter, draft: &Draft) -> Result<Counter, RowError> {
+    let mut counter = match draft.color {
+        Some((color, custom_color)) => Counter {
+            color,
+            custom_color,
+            ..counter.clone()
+        },
+        None => counter.clone(),
+    };
+
+    for tag in draft.tags.iter().flatten() {
+        if !counter.has_tag(tag) {
+            counter.tags.push(tag.clone());
+        }
+    }
+
+    let Some((kind, count)) = draft.count else {
+        return Ok(counter);
+    };
+    let rescaled = kind.rescale(count, counter.kind);
+    if counter.kind.rescale(rescaled, kind) != count {
+        return Err(RowError::Precision(kind.format(count), counter.kind));
+    }
+
+    let merged = counter.with_count(rescaled, EventKind::Set);
+    if merged.count == rescaled {
+        Ok(merged)
+    } else {
+        Err(RowError::OutOfRange(kind.format(count)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{export, models::Limits, storage::json::JsonStore};
+
+    fn table(contents: &str) -> Table {
+        Table::parse(contents).unwrap()
+    }
+
+    fn drafts(contents: &str) -> Vec<Result<Draft, RowError>> {
+        let table = table(contents);
+        table.drafts(&table.guess_mapping())
+    }
+
+    fn counter(title: &str, kind: ValueKind, count: i64) -> Counter {
+        Counter {
+            title: title.to_string(),
+            kind,
+            count,
+            ..Counter::default()
+        }
+    }
+
+    fn titles(counters: &[Counter]) -> Vec<&str> {
+        counters
+            .iter()
+            .map(|counter| counter.title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn parses_csv() {
+        let parsed = table("\u{feff}Title, Count\n Milk ,2\n\"Eggs, brown\",12,extra\n");
+
+        assert_eq!(parsed.headers, ["Title", "Count"]);
+        assert_eq!(
+            parsed.rows,
+            [vec!["Milk", "2"], vec!["Eggs, brown", "12", "extra"]]
+        );
+    }
+
+    #[test]
+    fn recognises_json_by_its_first_character() {
+        let list =
+            table(r#" [{"title": "Milk", "tags": ["a", "b"]}, {"count": 3, "title": null}]"#);
+        // Keys are sorted within each record, and new ones are added as they appear.
+        assert_eq!(list.headers, ["tags", "title", "count"]);
+        assert_eq!(list.rows, [vec!["a, b", "Milk", ""], vec!["", "", "3"]]);
+
+        let document = table(r#"{"version": 2, "counters": [{"title": "Milk"}]}"#);
+        assert_eq!(document.headers, ["title", "count", "color", "tags"]);
+        assert_eq!(document.rows, [vec!["Milk", "0", "System", ""]]);
+
+        // A CSV whose first cell happens to look like JSON is still read as JSON.
+        assert!(matches!(
+            Table::parse("[title]\nMilk"),
+            Err(ImportError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn reads_saved_counts_in_their_own_kind() {
+        let document = serde_json::json!({
+            "version": 2,
+            "counters": [
+                {"title": "Fuel", "count": 250, "kind": {"Decimal": {"places": 2}}},
+                {"title": "Balance", "count": 5, "kind": "Signed"},
+                {"title": "Paint", "count": 1, "color": "Red", "custom_color": "#123456"},
+            ],
+        });
+        let rows = drafts(&document.to_string());
+        let imported = apply(Vec::new(), rows, Mode::Replace).counters;
+
+        assert_eq!(imported[0].kind, ValueKind::Decimal { places: 2 });
+        assert_eq!(imported[0].count, 250);
+        assert_eq!(imported[1].kind, ValueKind::Signed);
+        assert_eq!(imported[1].count, 5);
+        assert_eq!(
+            imported[2].custom_color,
+            Some(Rgb::parse("#123456").unwrap())
+        );
+    }
+
+    #[test]
+    fn rejects_json_that_is_not_records_and_files_without_rows() {
+        assert!(matches!(
+            Table::parse("[1, 2]"),
+            Err(ImportError::NotRecords)
+        ));
+        assert!(matches!(
+            Table::parse(r#"{"counters": 3}"#),
+            Err(ImportError::NotRecords)
+        ));
+        assert!(matches!(Table::parse("[]"), Err(ImportError::Empty)));
+        assert!(matches!(
+            Table::parse("title,count\n"),
+            Err(ImportError::Empty)
+        ));
+    }
+
+    #[test]
+    fn guesses_columns_from_their_headers() {
+        let parsed = table("ID,Counter Title,Colour,Value,Labels,Name\n1,Milk,red,2,a,other\n");
+
+        assert_eq!(
+            parsed.guess_mapping(),
+            Mapping::from([
+                (Field::Title, 1),
+                (Field::Color, 2),
+                (Field::Count, 3),
+                (Field::Tags, 4),
+            ])
+        );
+        assert!(table("a,b\n1,2\n").guess_mapping().is_empty());
+    }
+
+    #[test]
+    fn reads_rows_and_reports_their_errors() {
+        let rows =
+            drafts("title,count,color,tags\nMilk,2.50,blue,a; b; A\n,1,,\nEggs,x,,\nHam,1,teal,\n");
+
+        assert_eq!(
+            rows[0],
+            Ok(Draft {
+                title: String::from("Milk"),
+                count: Some((ValueKind::Decimal { places: 2 }, 250)),
+                color: Some((Color::Blue, None)),
+                tags: Some(vec![String::from("a"), String::from("b")]),
+            })
+        );
+        assert_eq!(rows[1], Err(RowError::MissingTitle));
+        assert!(matches!(rows[2], Err(RowError::Count(..))));
+        assert!(matches!(rows[3], Err(RowError::Color(..))));
+    }
+
+    #[test]
+    fn merge_updates_counters_with_the_same_title() {
+        let existing = vec![
+            counter("Milk", ValueKind::Unsigned, 1),
+            counter("Eggs", ValueKind::Unsigned, 6),
+        ];
+        let outcome = apply(
+            existing,
+            drafts("title,count\nmilk,4\nHam,2\n"),
+            Mode::Merge,
+        );
+
+        assert_eq!(titles(&outcome.counters), ["Milk", "Eggs", "Ham"]);
+        assert_eq!(outcome.counters[0].count, 4);
+        assert_eq!(outcome.counters[1].count, 6);
+        assert!(outcome.rows.iter().all(Result::is_ok));
+    }
+
+    #[test]
+    fn replace_and_append_ignore_existing_titles() {
+        let existing = || vec![counter("Milk", ValueKind::Unsigned, 1)];
+        let rows = || drafts("title,count\nMilk,4\n");
+
+        let replaced = apply(existing(), rows(), Mode::Replace);
+        assert_eq!(titles(&replaced.counters), ["Milk"]);
+        assert_eq!(replaced.counters[0].count, 4);
+
+        let appended = apply(existing(), rows(), Mode::Append);
+        assert_eq!(titles(&appended.counters), ["Milk", "Milk"]);
+        assert_eq!(appended.counters[0].count, 1);
+        assert_eq!(appended.counters[1].count, 4);
+    }
+
+    #[test]
+    fn merge_rejects_counts_the_counter_cannot_hold() {
+        let existing = vec![
+            counter("Milk", ValueKind::Unsigned, 1),
+            Counter {
+                limits: Limits {
+                    max: Some(10),
+                    ..Limits::default()
+                },
+                ..counter("Eggs", ValueKind::Signed, 6)
+            },
+            counter("Fuel", ValueKind::Decimal { places: 1 }, 5),
+        ];
+        let outcome = apply(
+            existing.clone(),
+            drafts("title,count\nMilk,2.5\nMilk,-5\nEggs,12\nFuel,2\nFuel,2.50\n"),
+            Mode::Merge,
+        );
+
+        assert_eq!(
+            outcome.rows[0],
+            Err(RowError::Precision(
+                String::from("2.5"),
+                ValueKind::Unsigned
+            ))
+        );
+        assert_eq!(
+            outcome.rows[1],
+            Err(RowError::OutOfRange(String::from("-5")))
+        );
+        assert_eq!(
+            outcome.rows[2],
+            Err(RowError::OutOfRange(String::from("12")))
+        );
+        assert!(outcome.rows[3].is_ok());
+        assert!(outcome.rows[4].is_ok());
+
+        assert_eq!(outcome.counters[0], existing[0]);
+        assert_eq!(outcome.counters[1], existing[1]);
+        assert_eq!(outcome.counters[2].count, 25);
+    }
+
+    #[test]
+    fn reads_back_its_own_export() {
+        let counters = vec![
+            Counter {
+                color: Color::Green,
+                tags: vec![String::from("dairy"), String::from("cold, fresh")],
+                ..counter("Milk, \"whole\"", ValueKind::Unsigned, 3)
+            },
+            counter("Fuel", ValueKind::Decimal { places: 2 }, 250),
+            counter("Balance", ValueKind::Signed, -4),
+        ];
+        let store = JsonStore::new(
+            std::env::temp_dir().join(format!("tallyfy-import-{}.json", uuid::Uuid::new_v4())),
+        );
+        let csv = export::counters_csv(&store, &counters, &[]).unwrap();
+        let rows = drafts(std::str::from_utf8(&csv).unwrap());
+        let imported = apply(Vec::new(), rows, Mode::Replace).counters;
+
+        assert_eq!(imported.len(), counters.len());
+        for (imported, original) in imported.iter().zip(&counters) {
+            assert_eq!(imported.title, original.title);
+            assert_eq!(imported.kind, original.kind);
+            assert_eq!(imported.count, original.count);
+            assert_eq!(imported.color, original.color);
+        }
+        // Tags are joined with commas on export, so one holding a comma splits.
+        assert_eq!(imported[0].tags, ["dairy", "cold", "fresh"]);
+    }
+}
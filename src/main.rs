@@ -1,21 +1,52 @@
 #![allow(non_snake_case)]
 #![warn(clippy::pedantic, clippy::nursery)]
 
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc, time::Duration};
 
-use components::{list::CounterList, modal::Modal, navbar::Navbar};
+use components::{
+    dashboard::Dashboard, detail::CounterDetail, export_dialog::ExportButton, group::GroupHeader,
+    import_dialog::ImportButton, list::CounterList, modal::Modal, navbar::Navbar,
+};
 use dioxus::{
     desktop::{tao::dpi::Size, LogicalSize, WindowBuilder},
     prelude::*,
 };
 
-use dioxus_logger::tracing::{info, Level};
+use chrono::Local;
+use color::Rgb;
+use dioxus_logger::tracing::{error, info, Level};
 use enum_iterator::all;
-use models::{Color, Counter};
+use filter::{normalize, Filter};
+use history::{Coalesce, History};
+use icon::Icon;
+use models::{Color, Counter, EventKind, FilterPreset, Group, Limits};
+use query::Query;
+use schedule::ResetSchedule;
+use sort::SortMode;
+use state::AppState;
+use storage::{Document, StorageError, Store, Unsaved};
 use uuid::Uuid;
+use value::ValueKind;
 
+mod chart;
+mod color;
 mod components;
+mod export;
+mod filter;
+mod fuzzy;
+mod history;
+mod icon;
+mod import;
+mod markdown;
+mod migration;
 mod models;
+mod query;
+mod schedule;
+mod sort;
+mod state;
+mod stats;
+mod storage;
+mod value;
 
 const STYLE: Asset = asset!("./public/styles/tailwind/tailwind.css");
 
@@ -34,29 +65,181 @@ fn main() {
     LaunchBuilder::desktop().with_cfg(cfg).launch(App);
 }
 
+#[derive(Debug, Clone, PartialEq, Routable)]
+enum Route {
+    #[route("/")]
+    Home {},
+    #[route("/counter/:id")]
+    CounterDetail { id: Uuid },
+    #[route("/dashboard")]
+    Dashboard {},
+}
+
 #[component]
 fn App() -> Element {
-    let get_color_name = |color: Color| match color {
-        crate::models::Color::System => "base",
-        crate::models::Color::Red => "red",
-        crate::models::Color::Orange => "orange",
-        crate::models::Color::Yellow => "yellow",
-        crate::models::Color::Green => "green",
-        crate::models::Color::Blue => "blue",
-        crate::models::Color::Purple => "purple",
-    };
+    let mut load_error = use_signal(|| None::<String>);
+
+    let mut newer_data = use_signal(|| false);
+
+    let mut save_error = use_signal(|| None::<String>);
+
+    let mut presets = use_signal(Vec::<FilterPreset>::new);
+
+    let mut sort_mode = use_signal(SortMode::default);
+
+    let mut groups = use_signal(Vec::<Group>::new);
+
+    let store = use_context_provider(storage::open);
+
+    let mut unsaved = use_signal(Vec::<Unsaved>::new);
+
+    let mut revision = use_signal(|| 0_u64);
+
+    let load_store = store.clone();
+    let counters = use_signal(move || match load_store.load() {
+        Ok(document) => {
+            presets.set(document.presets);
+            sort_mode.set(document.sort);
+            groups.set(document.groups);
+            document.counters
+        }
+        Err(err) => {
+            error!("failed to load counters: {err}");
+            newer_data.set(matches!(err, StorageError::NewerDatabase(_)));
+            load_error.set(Some(err.to_string()));
+            Vec::new()
+        }
+    });
+
+    let save_store = store.clone();
+    // Persist after every mutation, handing the store the entries logged
+    // since the last save. Saving stays off while the file on disk could not
+    // be read, so a corrupt file is never overwritten by an empty list.
+    use_effect(move || {
+        let document = Document {
+            counters: counters(),
+            presets: presets(),
+            sort: sort_mode(),
+            groups: groups(),
+        };
+        if load_error().is_some() {
+            return;
+        }
+
+        let batches = std::mem::take(&mut *unsaved.write());
+        match save_store.save(&document, &batches) {
+            Ok(()) => {
+                save_error.set(None);
+                revision += 1;
+            }
+            Err(err) => {
+                error!("failed to save counters: {err}");
+                save_error.set(Some(err.to_string()));
+                // Kept for the next save, ahead of anything logged since.
+                unsaved.write().splice(0..0, batches);
+            }
+        }
+    });
 
-    let mut counters = use_signal(Vec::new);
+    let history = use_signal(History::<Vec<Counter>>::default);
 
-    let mut show_filter = use_signal(|| false);
+    let show_filter = use_signal(|| false);
 
-    let mut filter_colors = use_signal(|| {
+    let filter_colors = use_signal(|| {
         all::<Color>()
             .map(|color| (color, false))
             .collect::<HashMap<Color, bool>>()
     });
 
-    let mut filter_text = use_signal(String::new);
+    let filter_text = use_signal(String::new);
+
+    let filter_tags = use_signal(Vec::<String>::new);
+
+    let list_scroll = use_signal(|| 0.0);
+
+    let state = use_context_provider(|| AppState {
+        counters,
+        unsaved,
+        revision,
+        store: CopyValue::new(store),
+        history,
+        presets,
+        groups,
+        sort_mode,
+        load_error,
+        newer_data,
+        save_error,
+        show_filter,
+        filter_colors,
+        filter_text,
+        filter_tags,
+        list_scroll,
+    });
+
+    // Apply scheduled resets on start and keep checking while the app runs.
+    use_future(move || async move {
+        loop {
+            state.apply_due_resets(Local::now());
+            tokio::time::sleep(Duration::from_secs(30)).await;
+        }
+    });
+
+    rsx! {
+        Router::<Route> {}
+    }
+}
+
+#[component]
+fn Home() -> Element {
+    let state = use_context::<AppState>();
+    let AppState {
+        counters,
+        history,
+        mut presets,
+        mut groups,
+        mut sort_mode,
+        mut load_error,
+        newer_data,
+        save_error,
+        mut show_filter,
+        mut filter_colors,
+        mut filter_text,
+        mut filter_tags,
+        mut list_scroll,
+        ..
+    } = state;
+
+    let navigator = navigator();
+
+    // Coming back from another page, return to where the list was scrolled.
+    use_effect(move || {
+        let top = *list_scroll.peek();
+        document::eval(&format!("window.scrollTo(0, {top});"));
+    });
+
+    // Leaves the list for `route`, remembering how far it was scrolled.
+    let open_page = move |route: Route| {
+        spawn(async move {
+            if let Ok(top) = document::eval("return window.scrollY;").join::<f64>().await {
+                list_scroll.set(top);
+            }
+            navigator.push(route);
+        });
+    };
+
+    let open_counter = move |id: Uuid| open_page(Route::CounterDetail { id });
+
+    let store = use_context::<Rc<dyn Store>>();
+    let discard_saved_data = move |_| match store.backup_corrupt() {
+        Ok(backup) => {
+            info!("moved unreadable counters to {}", backup.display());
+            load_error.set(None);
+        }
+        Err(err) => {
+            error!("failed to back up counters: {err}");
+            load_error.set(Some(err.to_string()));
+        }
+    };
 
     let mut toggle_filter_color = move |color: Color| {
         let mut new_colors = filter_colors();
@@ -69,178 +252,462 @@ fn App() -> Element {
     let is_color_selected =
         move |color: Color| filter_colors().get(&color).is_some_and(|color| *color);
 
-    let filtered_counters = move || {
-        counters()
-            .into_iter()
-            .filter(|counter: &Counter| {
-                is_color_selected(counter.color)
-                    || (!filter_text().is_empty() && counter.title.contains(&filter_text()))
-            })
+    // Every tag in use, once each regardless of case or accents, sorted.
+    let all_tags = move || {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in counters().into_iter().flat_map(|counter| counter.tags) {
+            if !tags.iter().any(|known| normalize(known) == normalize(&tag)) {
+                tags.push(tag);
+            }
+        }
+        tags.sort_by_cached_key(|tag| normalize(tag));
+        tags
+    };
+
+    // A selected tag stops filtering once no counter has it any more, so
+    // deleting its last counter doesn't leave an empty list behind.
+    use_effect(move || {
+        let tags = all_tags();
+        let in_use =
+            |selected: &String| tags.iter().any(|tag| normalize(tag) == normalize(selected));
+        if !filter_tags.peek().iter().all(in_use) {
+            filter_tags.with_mut(|selected| selected.retain(in_use));
+        }
+    });
+
+    let is_tag_selected = move |tag: &str| {
+        filter_tags()
+            .iter()
+            .any(|selected| normalize(selected) == normalize(tag))
+    };
+
+    let mut toggle_filter_tag = move |tag: String| {
+        if is_tag_selected(&tag) {
+            filter_tags
+                .with_mut(|tags| tags.retain(|selected| normalize(selected) != normalize(&tag)));
+        } else {
+            filter_tags.write().push(tag);
+        }
+    };
+
+    let query = use_memo(move || Query::parse(&filter_text()));
+
+    let mut preset_name = use_signal(String::new);
+
+    let selected_colors = move || {
+        all::<Color>()
+            .filter(|color| is_color_selected(*color))
             .collect::<Vec<_>>()
     };
 
-    let mut handle_add_counter = move |counter: Counter| {
+    let is_preset_active = move |preset: &FilterPreset| {
+        preset.text == filter_text()
+            && preset.colors == selected_colors()
+            && preset.tags == filter_tags()
+    };
+
+    let mut handle_save_preset = move || {
+        let name = preset_name().trim().to_string();
+        if name.is_empty()
+            || query().is_err()
+            || (selected_colors().is_empty()
+                && filter_tags().is_empty()
+                && filter_text().trim().is_empty())
+        {
+            return;
+        }
+
+        let preset = FilterPreset {
+            id: Uuid::new_v4(),
+            name,
+            colors: selected_colors(),
+            tags: filter_tags(),
+            text: filter_text(),
+        };
+        // Saving under an existing name replaces that preset.
+        presets.with_mut(|presets| {
+            match presets
+                .iter_mut()
+                .find(|existing| existing.name == preset.name)
+            {
+                Some(existing) => {
+                    *existing = FilterPreset {
+                        id: existing.id,
+                        ..preset
+                    }
+                }
+                None => presets.push(preset),
+            }
+        });
+        preset_name.set(String::new());
+    };
+
+    let mut handle_apply_preset = move |preset: FilterPreset| {
+        filter_colors.set(
+            all::<Color>()
+                .map(|color| (color, preset.colors.contains(&color)))
+                .collect(),
+        );
+        filter_tags.set(preset.tags);
+        filter_text.set(preset.text);
+    };
+
+    let mut handle_delete_preset = move |id: Uuid| {
+        presets.with_mut(|presets| presets.retain(|preset| preset.id != id));
+    };
+
+    let filter = move || Filter {
+        colors: selected_colors().into_iter().collect(),
+        tags: filter_tags(),
+        query: query().unwrap_or_default(),
+    };
+
+    let filtered_counters = move || filter().apply(counters());
+
+    // Sorting only changes what is shown; `counters` keeps the manual order.
+    let visible_counters = move || {
+        let mut visible = if show_filter() {
+            filtered_counters()
+        } else {
+            counters()
+        };
+        sort_mode().sort(&mut visible);
+        visible
+    };
+
+    // Dragging and the move buttons only make sense while the list shows the
+    // manual order, not a sort or a ranking by filter score.
+    let is_reorderable = move || {
+        sort_mode() == SortMode::Manual
+            && !(show_filter() && query().is_ok_and(|query| query.has_fuzzy_terms()))
+    };
+
+    let commit = move |next: Vec<Counter>, coalesce: Option<Coalesce>| {
+        state.commit(next, coalesce);
+    };
+
+    let handle_undo = move || state.undo();
+
+    let handle_redo = move || state.redo();
+
+    let handle_add_counter = move |counter: Counter| {
         let combined: Vec<_> = counters()
             .into_iter()
             .chain(std::iter::once(counter))
             .collect();
 
-        counters.set(combined);
+        commit(combined, None);
     };
 
     let handle_increment = move |id: Uuid| {
-        counters.set(
-            counters()
-                .into_iter()
-                .map(|counter| {
-                    if counter.id == id {
-                        Counter {
-                            count: counter.count + 1,
-                            ..counter
-                        }
-                    } else {
-                        counter
-                    }
-                })
-                .collect(),
-        );
+        state.update(id, None, |counter| {
+            let count = counter.count.saturating_add(counter.step);
+            counter.with_count(count, EventKind::Increment)
+        });
     };
 
     let handle_decrement = move |id: Uuid| {
-        counters.set(
-            counters()
-                .into_iter()
-                .map(|counter| {
-                    if counter.id == id {
-                        Counter {
-                            count: counter.count - 1,
-                            ..counter
-                        }
-                    } else {
-                        counter
-                    }
-                })
-                .collect(),
-        );
+        state.update(id, None, |counter| {
+            let count = counter.count.saturating_sub(counter.step);
+            counter.with_count(count, EventKind::Decrement)
+        });
     };
 
     let handle_ontitlechange = move |(id, title): (Uuid, String)| {
-        counters.set(
-            counters()
-                .into_iter()
-                .map(|counter| {
-                    if counter.id == id {
-                        Counter {
-                            title: title.clone(),
-                            ..counter
-                        }
-                    } else {
-                        counter
-                    }
-                })
-                .collect(),
-        );
+        state.update(id, Some(Coalesce::Title(id)), |counter| Counter {
+            title: title.clone(),
+            ..counter
+        });
     };
 
-    let handle_onvaluechange = move |(id, value): (Uuid, usize)| {
-        counters.set(
+    let handle_onvaluechange = move |(id, value): (Uuid, i64)| {
+        state.update(id, Some(Coalesce::Value(id)), |counter| {
+            counter.with_count(value, EventKind::Set)
+        });
+    };
+
+    let handle_onstepchange = move |(id, step): (Uuid, i64)| {
+        state.update(id, Some(Coalesce::Step(id)), |counter| Counter {
+            step,
+            ..counter
+        });
+    };
+
+    let handle_onkindchange = move |(id, kind): (Uuid, ValueKind)| {
+        state.update(id, None, |counter| counter.with_kind(kind));
+    };
+
+    let handle_onlimitschange = move |(id, limits): (Uuid, Limits)| {
+        state.update(id, Some(Coalesce::Limits(id)), |counter| {
+            counter.with_limits(limits)
+        });
+    };
+
+    let handle_onresetvaluechange = move |(id, reset_value): (Uuid, i64)| {
+        state.update(id, Some(Coalesce::ResetValue(id)), |counter| Counter {
+            reset_value,
+            ..counter
+        });
+    };
+
+    let handle_onreset = move |id: Uuid| {
+        state.update(id, None, Counter::reset);
+    };
+
+    let handle_onschedulechange = move |(id, schedule): (Uuid, Option<ResetSchedule>)| {
+        state.update(id, Some(Coalesce::Schedule(id)), |counter| Counter {
+            schedule,
+            ..counter
+        });
+    };
+
+    let handle_reset_visible = move |_| {
+        let visible = visible_counters()
+            .into_iter()
+            .map(|counter| counter.id)
+            .collect::<Vec<_>>();
+
+        commit(
             counters()
                 .into_iter()
                 .map(|counter| {
-                    if counter.id == id {
-                        Counter {
-                            count: value,
-                            ..counter
-                        }
+                    if visible.contains(&counter.id) {
+                        counter.reset()
                     } else {
                         counter
                     }
                 })
                 .collect(),
+            None,
         );
     };
 
     let handle_ondelete = move |id: Uuid| {
-        counters.set(
+        commit(
             counters()
                 .into_iter()
                 .filter(|counter| counter.id != id)
                 .collect(),
+            None,
         );
     };
 
-    let handle_onmoveup = move |id: Uuid| {
-        let mut old_counters = counters();
-        let pos = counters()
+    // Counters pointing at a group that no longer exists are listed as ungrouped.
+    let group_of = move |counter: &Counter| {
+        counter
+            .group
+            .filter(|id| groups().iter().any(|group| group.id == *id))
+    };
+
+    // The counters sharing a group with `id`, in manual order. Moving a
+    // counter only reorders these, so each group keeps its own order.
+    let group_members = move |id: Uuid| {
+        let counters = counters();
+        let group = counters
             .iter()
-            .position(|counter| counter.id == id)
-            .unwrap();
+            .find(|counter| counter.id == id)
+            .and_then(group_of);
+
+        counters
+            .into_iter()
+            .filter(|counter| group_of(counter) == group)
+            .collect::<Vec<_>>()
+    };
+
+    let handle_onmoveup = move |id: Uuid| {
+        if !is_reorderable() {
+            return;
+        }
+
+        let mut members = group_members(id);
+        let Some(pos) = members.iter().position(|counter| counter.id == id) else {
+            return;
+        };
 
         if pos > 0 {
-            old_counters.swap(pos, pos - 1);
-            counters.set(old_counters);
+            members.swap(pos, pos - 1);
+            commit(apply_order(counters(), &members), None);
         }
     };
 
     let handle_onmovetop = move |id: Uuid| {
-        let mut new_counters = counters();
-        let pos = new_counters
-            .iter()
-            .position(|counter| counter.id == id)
-            .unwrap();
-        let counter = new_counters.remove(pos);
-        new_counters.insert(0, counter);
+        if !is_reorderable() {
+            return;
+        }
 
-        counters.set(new_counters);
+        let mut members = group_members(id);
+        let Some(pos) = members.iter().position(|counter| counter.id == id) else {
+            return;
+        };
+        let counter = members.remove(pos);
+        members.insert(0, counter);
+
+        commit(apply_order(counters(), &members), None);
     };
 
     let handle_onmovedown = move |id: Uuid| {
-        let mut new_counters = counters();
-        let pos = counters()
-            .iter()
-            .position(|counter| counter.id == id)
-            .unwrap();
+        if !is_reorderable() {
+            return;
+        }
 
-        if pos < counters().len().saturating_sub(1) {
-            new_counters.swap(pos, pos + 1);
-            counters.set(new_counters);
+        let mut members = group_members(id);
+        let Some(pos) = members.iter().position(|counter| counter.id == id) else {
+            return;
+        };
+
+        if pos < members.len().saturating_sub(1) {
+            members.swap(pos, pos + 1);
+            commit(apply_order(counters(), &members), None);
         }
     };
 
     let handle_onmovebottom = move |id: Uuid| {
+        if !is_reorderable() {
+            return;
+        }
+
+        let mut members = group_members(id);
+        let Some(pos) = members.iter().position(|counter| counter.id == id) else {
+            return;
+        };
+        let counter = members.remove(pos);
+        members.push(counter);
+
+        commit(apply_order(counters(), &members), None);
+    };
+
+    // A ranked or sorted list must never be written back as the manual order.
+    let handle_reorder = move |reordered: Vec<Counter>| {
+        if is_reorderable() {
+            commit(apply_order(counters(), &reordered), None);
+        }
+    };
+
+    let handle_onsetcolor = move |(id, color): (Uuid, Color)| {
+        state.update(id, None, |counter| Counter {
+            color,
+            custom_color: None,
+            ..counter
+        });
+    };
+
+    let handle_ontagschange = move |(id, tags): (Uuid, Vec<String>)| {
+        state.update(id, None, |counter| Counter {
+            tags: tags.clone(),
+            ..counter
+        });
+    };
+
+    let handle_onnoteschange = move |(id, notes): (Uuid, String)| {
+        state.update(id, Some(Coalesce::Notes(id)), |counter| Counter {
+            notes: notes.clone(),
+            ..counter
+        });
+    };
+
+    let handle_oniconchange = move |(id, icon): (Uuid, Option<Icon>)| {
+        state.update(id, None, |counter| Counter {
+            icon: icon.clone(),
+            ..counter
+        });
+    };
+
+    let handle_ongroupchange = move |(id, group): (Uuid, Option<Uuid>)| {
         let mut new_counters = counters();
-        let pos = new_counters
-            .iter()
-            .position(|counter| counter.id == id)
-            .unwrap();
+        let Some(pos) = new_counters.iter().position(|counter| counter.id == id) else {
+            return;
+        };
 
+        // A counter joins its new group at the end.
         let counter = new_counters.remove(pos);
-        new_counters.push(counter);
+        new_counters.push(Counter { group, ..counter });
 
-        counters.set(new_counters);
+        commit(new_counters, None);
     };
 
-    let handle_reorder = move |new_counters| {
-        counters.set(new_counters);
+    let mut handle_add_group = move || groups.write().push(Group::default());
+
+    let handle_add_to_group = move |group: Uuid| {
+        handle_add_counter(Counter {
+            group: Some(group),
+            ..Counter::default()
+        });
     };
 
-    let handle_onsetcolor = move |(id, color): (Uuid, Color)| {
-        counters.set(
-            counters()
-                .into_iter()
-                .map(|counter| {
-                    if counter.id == id {
-                        Counter { color, ..counter }
-                    } else {
-                        counter
-                    }
-                })
-                .collect(),
-        );
+    let handle_toggle_group = move |id: Uuid| {
+        groups.with_mut(|groups| {
+            for group in groups.iter_mut().filter(|group| group.id == id) {
+                group.collapsed = !group.collapsed;
+            }
+        });
+    };
+
+    let handle_rename_group = move |(id, name): (Uuid, String)| {
+        groups.with_mut(|groups| {
+            for group in groups.iter_mut().filter(|group| group.id == id) {
+                group.name.clone_from(&name);
+            }
+        });
+    };
+
+    let handle_delete_group = move |id: Uuid| state.delete_group(id);
+
+    let ungrouped_counters = move || {
+        visible_counters()
+            .into_iter()
+            .filter(|counter| group_of(counter).is_none())
+            .collect::<Vec<_>>()
+    };
+
+    // Each group with its visible counters. Groups with no matches are left
+    // out while a filter is narrowing the list.
+    let group_sections = move || {
+        let visible = visible_counters();
+        let filtering = show_filter() && !filter().is_empty();
+        groups()
+            .into_iter()
+            .filter_map(|group| {
+                let members = visible
+                    .iter()
+                    .filter(|counter| group_of(counter) == Some(group.id))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                (!filtering || !members.is_empty()).then_some((group, members))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let subtotal = |counters: &[Counter]| {
+        let (kind, sum) =
+            value::total(counters.iter().map(|counter| (counter.kind, counter.count)));
+        kind.format(sum)
+    };
+
+    let highlights = move || {
+        if show_filter() {
+            filter().highlights(&counters())
+        } else {
+            HashMap::new()
+        }
+    };
+
+    // Custom colors are filed under the nearest named color for filtering and sorting.
+    let handle_oncustomcolorchange = move |(id, rgb): (Uuid, Rgb)| {
+        state.update(id, Some(Coalesce::Color(id)), |counter| Counter {
+            color: rgb.nearest_preset(),
+            custom_color: Some(rgb),
+            ..counter
+        });
     };
 
     rsx! {
-        div { class: "round",
+        div {
+            class: "round min-h-screen outline-none",
+            tabindex: 0,
+            onmounted: move |e| async move {
+                _ = e.set_focus(true).await;
+            },
+            onkeydown: move |e| state.handle_shortcut(&e),
             Navbar {
                 start_content: rsx! {
                     button {
@@ -251,6 +718,26 @@ fn App() -> Element {
                             src: asset!("public/assets/plus.svg"),
                         }
                     }
+                    button {
+                        class: "btn btn-ghost btn-circle tooltip tooltip-bottom",
+                        "data-tip": "Undo",
+                        disabled: !history.read().can_undo(),
+                        onclick: move |_| handle_undo(),
+                        img {
+                            class: "w-1/2 h-1/2 dark:invert",
+                            src: asset!("public/assets/undo.svg"),
+                        }
+                    }
+                    button {
+                        class: "btn btn-ghost btn-circle tooltip tooltip-bottom",
+                        "data-tip": "Redo",
+                        disabled: !history.read().can_redo(),
+                        onclick: move |_| handle_redo(),
+                        img {
+                            class: "w-1/2 h-1/2 dark:invert",
+                            src: asset!("public/assets/redo.svg"),
+                        }
+                    }
                 },
                 center_content: rsx! {
                     p { class: "text-xl select-none", "Tallyfy" }
@@ -267,6 +754,38 @@ fn App() -> Element {
                             src: asset!("public/assets/search.svg"),
                         }
                     }
+                    button {
+                        class: "btn btn-ghost btn-circle tooltip tooltip-bottom",
+                        "data-tip": "Reset visible counters",
+                        disabled: visible_counters().is_empty(),
+                        "onclick": "reset_modal.showModal()",
+                        img {
+                            class: "w-1/2 h-1/2 dark:invert",
+                            src: asset!("public/assets/reset.svg"),
+                        }
+                    }
+                    Modal { id: "reset_modal",
+                        h1 { class: "text-lg", "Reset {visible_counters().len()} counters?" }
+                        p { class: "py-2 text-sm",
+                            "Each counter returns to its reset value. The current values are kept as sessions in their history."
+                        }
+                        div { class: "modal-action",
+                            button { class: "btn", "Cancel" }
+                            button { class: "btn btn-error", onclick: handle_reset_visible, "Reset" }
+                        }
+                    }
+                    ImportButton {}
+                    ExportButton {}
+                    div { class: "tooltip tooltip-bottom", "data-tip": "Dashboard",
+                        button {
+                            class: "btn btn-ghost btn-circle",
+                            onclick: move |_| open_page(Route::Dashboard {}),
+                            img {
+                                class: "w-1/2 h-1/2 dark:invert",
+                                src: asset!("public/assets/chart.svg"),
+                            }
+                        }
+                    }
                     button { class: "btn btn-ghost btn-circle", "onclick": "about_modal.showModal()",
                         img {
                             class: "w-1/2 h-1/2 dark:invert",
@@ -281,6 +800,21 @@ fn App() -> Element {
 
             div { class: "flex flex-col",
 
+                if let Some(err) = load_error() {
+                    div { role: "alert", class: "alert alert-error mx-14 w-auto",
+                        if newer_data() {
+                            span { "Saved counters could not be loaded: {err}. Changes will not be saved until Tallyfy is updated." }
+                        } else {
+                            span { "Saved counters could not be loaded: {err}. Changes will not be saved." }
+                            button { class: "btn btn-sm", onclick: discard_saved_data, "Back up and start fresh" }
+                        }
+                    }
+                } else if let Some(err) = save_error() {
+                    div { role: "alert", class: "alert alert-warning mx-14 w-auto",
+                        span { "Counters could not be saved: {err}" }
+                    }
+                }
+
                 if show_filter() {
                     div { class: "flex flex-col items-center justify-center",
                         div { class: "flex space-x-0.5 flex-row",
@@ -296,8 +830,7 @@ fn App() -> Element {
                                             if matches!(color, Color::System) {
                                                 String::new()
                                             } else {
-                                                let color = get_color_name(color);
-                                                format!("bg-{color}-500")
+                                                format!("bg-{}-500", color.palette_name())
                                             },
                                         ),
                                         onclick: move |_| {
@@ -307,9 +840,31 @@ fn App() -> Element {
                                 }
                             }
                         }
-                        label { class: "input input-bordered flex items-center gap-2 w-1/2",
+                        if !all_tags().is_empty() {
+                            div { class: "flex flex-wrap justify-center gap-1 w-1/2 my-2",
+                                for tag in all_tags() {
+                                    button {
+                                        class: format!(
+                                            "badge {}",
+                                            if is_tag_selected(&tag) { "badge-secondary" } else { "badge-outline" },
+                                        ),
+                                        aria_pressed: is_tag_selected(&tag),
+                                        onclick: {
+                                            let tag = tag.clone();
+                                            move |_| toggle_filter_tag(tag.clone())
+                                        },
+                                        "{tag}"
+                                    }
+                                }
+                            }
+                        }
+                        label {
+                            class: format!(
+                                "input input-bordered flex items-center gap-2 w-1/2 {}",
+                                if query().is_err() { "input-error" } else { "" },
+                            ),
                             input {
-                                placeholder: "Filter by text...",
+                                placeholder: "Filter, e.g. color:red tag:warehouse count>10 \"exact phrase\" -excluded",
                                 r#type: "text",
                                 class: "grow",
                                 value: filter_text(),
@@ -321,24 +876,200 @@ fn App() -> Element {
                                 src: asset!("public/assets/search.svg"),
                             }
                         }
+                        if let Err(err) = query() {
+                            p { class: "text-xs text-error w-1/2 mt-1", "{err}" }
+                        }
+                        if !presets().is_empty() {
+                            div { class: "flex flex-wrap justify-center gap-1 w-1/2 mt-2",
+                                for preset in presets() {
+                                    div {
+                                        key: "{preset.id}",
+                                        class: format!(
+                                            "badge gap-1 cursor-pointer {}",
+                                            if is_preset_active(&preset) { "badge-primary" } else { "badge-outline" },
+                                        ),
+                                        span {
+                                            onclick: {
+                                                let preset = preset.clone();
+                                                move |_| handle_apply_preset(preset.clone())
+                                            },
+                                            "{preset.name}"
+                                        }
+                                        button {
+                                            class: "opacity-60 hover:opacity-100",
+                                            aria_label: "Delete preset {preset.name}",
+                                            onclick: move |_| handle_delete_preset(preset.id),
+                                            "✕"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        div { class: "flex flex-row items-center gap-1 mt-2",
+                            input {
+                                class: "input input-bordered input-sm",
+                                placeholder: "Preset name",
+                                r#type: "text",
+                                value: preset_name(),
+                                oninput: move |e| preset_name.set(e.value()),
+                                onkeydown: move |e| {
+                                    if e.key() == Key::Enter {
+                                        handle_save_preset();
+                                    }
+                                },
+                            }
+                            button {
+                                class: "btn btn-sm",
+                                disabled: preset_name().trim().is_empty() || query().is_err() || filter().is_empty(),
+                                onclick: move |_| handle_save_preset(),
+                                "Save preset"
+                            }
+                            button {
+                                class: "btn btn-ghost btn-sm",
+                                disabled: filter().is_empty() && filter_text().is_empty(),
+                                onclick: move |_| {
+                                    filter_colors.set(all::<Color>().map(|color| (color, false)).collect());
+                                    filter_tags.set(Vec::new());
+                                    filter_text.set(String::new());
+                                },
+                                "Clear filter"
+                            }
+                        }
                     }
                 }
 
-                CounterList {
-                    counters: if show_filter() { filtered_counters() } else { counters() },
-                    onincrement: handle_increment,
-                    ondecrement: handle_decrement,
-                    onreorder: handle_reorder,
-                    ontitlechange: handle_ontitlechange,
-                    onvaluechange: handle_onvaluechange,
-                    ondelete: handle_ondelete,
-                    onmovedown: handle_onmovedown,
-                    onmovebottom: handle_onmovebottom,
-                    onmoveup: handle_onmoveup,
-                    onmovetop: handle_onmovetop,
-                    onsetcolor: handle_onsetcolor,
+                div { class: "flex justify-end mx-14 mt-2",
+                    select {
+                        class: "select select-bordered select-sm",
+                        aria_label: "Sort counters",
+                        onchange: move |e| {
+                            if let Some(mode) = all::<SortMode>().find(|mode| format!("{mode:?}") == e.value()) {
+                                sort_mode.set(mode);
+                            }
+                        },
+                        for mode in all::<SortMode>() {
+                            option {
+                                value: "{mode:?}",
+                                selected: sort_mode() == mode,
+                                "Sort: {mode.label()}"
+                            }
+                        }
+                    }
+                }
+
+                if groups().is_empty() || !ungrouped_counters().is_empty() {
+                    CounterList {
+                        counters: ungrouped_counters(),
+                        groups: groups(),
+                        reorderable: is_reorderable(),
+                        highlights: highlights(),
+                        onincrement: handle_increment,
+                        ondecrement: handle_decrement,
+                        onreorder: handle_reorder,
+                        ontitlechange: handle_ontitlechange,
+                        onvaluechange: handle_onvaluechange,
+                        onstepchange: handle_onstepchange,
+                        onlimitschange: handle_onlimitschange,
+                        onkindchange: handle_onkindchange,
+                        onresetvaluechange: handle_onresetvaluechange,
+                        onreset: handle_onreset,
+                        onschedulechange: handle_onschedulechange,
+                        ongroupchange: handle_ongroupchange,
+                        ontagschange: handle_ontagschange,
+                        onnoteschange: handle_onnoteschange,
+                        oniconchange: handle_oniconchange,
+                        onopen: open_counter,
+                        ondelete: handle_ondelete,
+                        onmovedown: handle_onmovedown,
+                        onmovebottom: handle_onmovebottom,
+                        onmoveup: handle_onmoveup,
+                        onmovetop: handle_onmovetop,
+                        onsetcolor: handle_onsetcolor,
+                        oncustomcolorchange: handle_oncustomcolorchange,
+                    }
+                }
+
+                for (group , members) in group_sections() {
+                    div { key: "{group.id}",
+                        GroupHeader {
+                            group: group.clone(),
+                            size: members.len(),
+                            subtotal: subtotal(&members),
+                            ontoggle: handle_toggle_group,
+                            onrename: handle_rename_group,
+                            onaddcounter: handle_add_to_group,
+                            ondelete: handle_delete_group,
+                        }
+                        if !group.collapsed {
+                            CounterList {
+                                counters: members,
+                                groups: groups(),
+                                reorderable: is_reorderable(),
+                                highlights: highlights(),
+                                onincrement: handle_increment,
+                                ondecrement: handle_decrement,
+                                onreorder: handle_reorder,
+                                ontitlechange: handle_ontitlechange,
+                                onvaluechange: handle_onvaluechange,
+                                onstepchange: handle_onstepchange,
+                                onlimitschange: handle_onlimitschange,
+                                onkindchange: handle_onkindchange,
+                                onresetvaluechange: handle_onresetvaluechange,
+                                onreset: handle_onreset,
+                                onschedulechange: handle_onschedulechange,
+                                ongroupchange: handle_ongroupchange,
+                                ontagschange: handle_ontagschange,
+                                onnoteschange: handle_onnoteschange,
+                                oniconchange: handle_oniconchange,
+                                onopen: open_counter,
+                                ondelete: handle_ondelete,
+                                onmovedown: handle_onmovedown,
+                                onmovebottom: handle_onmovebottom,
+                                onmoveup: handle_onmoveup,
+                                onmovetop: handle_onmovetop,
+                                onsetcolor: handle_onsetcolor,
+                                oncustomcolorchange: handle_oncustomcolorchange,
+                            }
+                        }
+                    }
+                }
+
+                div { class: "flex justify-center my-4",
+                    button {
+                        class: "btn btn-ghost btn-sm",
+                        onclick: move |_| handle_add_group(),
+                        "New group"
+                    }
                 }
             }
         }
     }
 }
+
+/// Applies the order of `reordered` to `counters`.
+///
+/// `reordered` may be a filtered subset: the slots held by its counters are
+/// refilled in the new order while hidden counters keep their positions.
+fn apply_order(counters: Vec<Counter>, reordered: &[Counter]) -> Vec<Counter> {
+    let mut moved = reordered
+        .iter()
+        .filter_map(|item| {
+            counters
+                .iter()
+                .find(|counter| counter.id == item.id)
+                .cloned()
+        })
+        .collect::<Vec<_>>()
+        .into_iter();
+
+    counters
+        .into_iter()
+        .map(|counter| {
+            if reordered.iter().any(|item| item.id == counter.id) {
+                moved.next().unwrap_or(counter)
+            } else {
+                counter
+            }
+        })
+        .collect()
+}
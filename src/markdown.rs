@@ -0,0 +1,142 @@
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+
+/// URL schemes a link or image in the notes may use. Anything else, such as
+/// `javascript:` or `data:`, is dropped.
+const ALLOWED_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+/// Renders Markdown notes to HTML.
+///
+/// Raw HTML in the notes is shown as text rather than passed through, and
+/// links and images keep their destination only if it is relative or uses
+/// one of [`ALLOWED_SCHEMES`], so notes can't inject markup or scripts into
+/// the app.
+pub fn to_html(markdown: &str) -> String {
+    let options =
+        Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS;
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        event => event,
+    });
+
+    let mut output = String::new();
+    html::push_html(&mut output, events);
+    output
+}
+
+/// `url` if it is relative or uses an allowed scheme, otherwise empty.
+fn safe_url(url: CowStr<'_>) -> CowStr<'_> {
+    // Browsers ignore whitespace and control characters in a scheme, so
+    // `java\tscript:` would still run.
+    let cleaned = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect::<String>();
+    let scheme = cleaned
+        .split_once(':')
+        .map(|(scheme, _)| scheme)
+        .filter(|scheme| !scheme.contains(['/', '?', '#']));
+
+    match scheme {
+        Some(scheme)
+            if !ALLOWED_SCHEMES
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(scheme)) =>
+        {
+            CowStr::Borrowed("")
+        }
+        _ => url,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_html_is_shown_as_text() {
+        let html = to_html("<script>alert(1)</script>\n\nhi <img src=x onerror=alert(1)>");
+
+        assert!(!html.contains("<script"), "{html}");
+        assert!(!html.contains("<img"), "{html}");
+        assert!(
+            html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"),
+            "{html}"
+        );
+        assert!(
+            html.contains("&lt;img src=x onerror=alert(1)&gt;"),
+            "{html}"
+        );
+    }
+
+    #[test]
+    fn unsafe_link_and_image_urls_are_dropped() {
+        for markdown in [
+            "[click](javascript:alert(1))",
+            "[click](JavaScript:alert(1))",
+            "[click](<java\tscript:alert(1)>)",
+            "[click][ref]\n\n[ref]: javascript:alert(1)",
+            "<javascript:alert(1)>",
+            "![pic](javascript:alert(1))",
+            "![pic](data:image/svg+xml;base64,PHN2Zz4=)",
+            "[click](vbscript:msgbox)",
+        ] {
+            let html = to_html(markdown);
+            assert!(
+                html.contains("href=\"\"") || html.contains("src=\"\""),
+                "{markdown} gave {html}"
+            );
+        }
+
+        assert_eq!(
+            to_html("[click](javascript:alert(1))"),
+            "<p><a href=\"\">click</a></p>\n"
+        );
+        assert_eq!(
+            to_html("![pic](javascript:alert(1))"),
+            "<p><img src=\"\" alt=\"pic\" /></p>\n"
+        );
+    }
+
+    #[test]
+    fn web_mail_and_relative_urls_are_kept() {
+        for (markdown, href) in [
+            (
+                "[site](https://example.com/a?b=c)",
+                "https://example.com/a?b=c",
+            ),
+            ("[site](HTTP://example.com)", "HTTP://example.com"),
+            ("[mail](mailto:me@example.com)", "mailto:me@example.com"),
+            ("[doc](notes/list.md)", "notes/list.md"),
+            ("[top](#heading)", "#heading"),
+            ("[query](search?q=a:b)", "search?q=a:b"),
+        ] {
+            let html = to_html(markdown);
+            assert!(
+                html.contains(&format!("href=\"{href}\"")),
+                "{markdown} gave {html}"
+            );
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+/// Version written by this build. Older documents are upgraded one version
+/// at a time on the raw JSON before they are deserialized.
+///
+/// To change the schema, bump this, append a migration from the previous
+/// version to [`MIGRATIONS`] and add an example of the previous version to
+/// `fixtures/storage`.
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// Key of the version in the saved document.
+pub const VERSION_KEY: &str = "version";
+
+type Migration = fn(Value) -> Result<Value, MigrationError>;
+
+/// `MIGRATIONS[n]` upgrades a document from version `n` to `n + 1`, so there
+/// is one for every version before [`SCHEMA_VERSION`].
+const MIGRATIONS: &[Migration] = &[from_v0, from_v1];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationError {
+    /// Written by a newer build, which may have stored things this one would lose.
+    TooNew(u64),
+    /// The shape does not match the version it claims.
+    Malformed { version: u64, reason: &'static str },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooNew(version) => write!(
+                f,
+                "they were saved by a newer version of Tallyfy (format {version}, this build reads up to {SCHEMA_VERSION})"
+            ),
+            Self::Malformed { version, reason } => {
+                write!(f, "format {version} is malformed: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// The schema version of a saved document.
+///
+/// Version 0 is the bare list of counters the first builds saved. Version 1
+/// added filter presets, so the list moved into an object, but had no
+/// version key yet.
+pub fn version_of(value: &Value) -> u64 {
+    match value {
+        Value::Array(_) => 0,
+        Value::Object(document) => document
+            .get(VERSION_KEY)
+            .and_then(Value::as_u64)
+            .unwrap_or(1),
+        _ => SCHEMA_VERSION,
+    }
+}
+
+/// Upgrades `value` to the current schema.
+pub fn migrate(mut value: Value) -> Result<Value, MigrationError> {
+    let saved = version_of(&value);
+    if saved > SCHEMA_VERSION {
+        return Err(MigrationError::TooNew(saved));
+    }
+
+    for migration in MIGRATIONS
+        .iter()
+        .skip(usize::try_from(saved).unwrap_or(usize::MAX))
+    {
+        value = migration(value)?;
+    }
+
+    Ok(value)
+}
+
+/// Wraps the bare list of counters in a document.
+fn from_v0(value: Value) -> Result<Value, MigrationError> {
+    let Value::Array(counters) = value else {
+        return Err(MigrationError::Malformed {
+            version: 0,
+            reason: "expected a list of counters",
+        });
+    };
+
+    let mut document = Map::new();
+    document.insert(String::from("counters"), Value::Array(counters));
+    Ok(Value::Object(document))
+}
+
+/// Adds the version key. Everything else in version 1 is read as it is,
+/// with defaults for the fields added since.
+fn from_v1(value: Value) -> Result<Value, MigrationError> {
+    let Value::Object(mut document) = value else {
+        return Err(MigrationError::Malformed {
+            version: 1,
+            reason: "expected an object",
+        });
+    };
+    if !document.get("counters").is_some_and(Value::is_array) {
+        return Err(MigrationError::Malformed {
+            version: 1,
+            reason: "expected a list of counters",
+        });
+    }
+
+    document.insert(String::from(VERSION_KEY), Value::from(2_u64));
+    Ok(Value::Object(document))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        color::Rgb,
+        models::{Color, Limits},
+        schedule::Repeat,
+        sort::SortMode,
+        storage::Document,
+        value::ValueKind,
+    };
+
+    const V0: &str = include_str!("../fixtures/storage/v0.json");
+    const V1: &str = include_str!("../fixtures/storage/v1.json");
+    const V2: &str = include_str!("../fixtures/storage/v2.json");
+
+    fn load(fixture: &str) -> Document {
+        let value = serde_json::from_str(fixture).unwrap();
+        serde_json::from_value(migrate(value).unwrap()).unwrap()
+    }
+
+    fn id(text: &str) -> Uuid {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn tells_versions_apart() {
+        for (fixture, version) in [(V0, 0), (V1, 1), (V2, 2)] {
+            assert_eq!(version_of(&serde_json::from_str(fixture).unwrap()), version);
+        }
+    }
+
+    #[test]
+    fn reads_the_bare_list_of_v0() {
+        let document = load(V0);
+
+        assert_eq!(document.counters.len(), 2);
+        let visitors = &document.counters[0];
+        assert_eq!(visitors.id, id("5b0c6c8e-2f4e-4a53-9f3a-1d2f0a7c9e11"));
+        assert_eq!(visitors.title, "Visitors");
+        assert_eq!(visitors.count, 12);
+        assert_eq!(visitors.color, Color::Green);
+        assert_eq!(visitors.step, 1);
+        assert_eq!(visitors.kind, ValueKind::Unsigned);
+        assert!(visitors.events.is_empty());
+
+        let returns = &document.counters[1];
+        assert_eq!(returns.title, "Returns, damaged");
+        assert_eq!(returns.step, 2);
+        assert_eq!(returns.events.len(), 2);
+        assert_eq!(
+            returns.limits,
+            Limits {
+                min: Some(0),
+                max: None,
+                goal: Some(10),
+            }
+        );
+
+        assert!(document.presets.is_empty());
+        assert!(document.groups.is_empty());
+        assert_eq!(document.sort, SortMode::Manual);
+    }
+
+    #[test]
+    fn reads_the_presets_and_schedules_of_v1() {
+        let document = load(V1);
+
+        assert_eq!(document.counters.len(), 2);
+        let visitors = &document.counters[0];
+        assert_eq!(visitors.sessions.len(), 1);
+        assert_eq!(visitors.sessions[0].value, 40);
+        assert_eq!(
+            visitors.schedule.map(|schedule| schedule.repeat),
+            Some(Repeat::Daily)
+        );
+        assert!(visitors.tags.is_empty());
+        assert_eq!(visitors.group, None);
+
+        let rainfall = &document.counters[1];
+        assert_eq!(rainfall.kind, ValueKind::Decimal { places: 1 });
+        assert_eq!(rainfall.kind.format(rainfall.count), "12.5");
+
+        assert_eq!(document.presets.len(), 1);
+        assert_eq!(document.presets[0].name, "Weather");
+        assert_eq!(document.presets[0].colors, [Color::Blue]);
+        assert!(document.presets[0].tags.is_empty());
+        assert!(document.groups.is_empty());
+        assert_eq!(document.sort, SortMode::Manual);
+    }
+
+    #[test]
+    fn reads_v2_as_it_is() {
+        let value = serde_json::from_str::<Value>(V2).unwrap();
+        assert_eq!(migrate(value.clone()), Ok(value));
+
+        let document = load(V2);
+        assert_eq!(document.counters.len(), 2);
+        let boxes = &document.counters[0];
+        assert_eq!(boxes.title, "Boxes, \"large\"");
+        assert_eq!(boxes.custom_color, Some(Rgb::new(0x1e, 0x90, 0xff)));
+        assert_eq!(
+            boxes.group,
+            Some(id("314c48d9-7694-40f6-bff2-07ce1ce716a8"))
+        );
+        assert_eq!(boxes.tags, ["stock"]);
+        assert_eq!(
+            boxes.schedule.map(|schedule| schedule.repeat),
+            Some(Repeat::Weekly { weekday: 0 })
+        );
+
+        assert_eq!(document.presets.len(), 1);
+        assert_eq!(document.presets[0].tags, ["stock"]);
+        assert_eq!(document.sort, SortMode::Title);
+        assert_eq!(document.groups.len(), 1);
+        assert_eq!(document.groups[0].name, "Warehouse");
+        assert!(!document.groups[0].collapsed);
+    }
+
+    #[test]
+    fn refuses_documents_from_a_newer_build() {
+        let value = json!({ "version": SCHEMA_VERSION + 1, "counters": [] });
+        assert_eq!(
+            migrate(value),
+            Err(MigrationError::TooNew(SCHEMA_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn reports_documents_that_do_not_match_their_version() {
+        assert_eq!(
+            from_v0(json!({ "counters": [] })),
+            Err(MigrationError::Malformed {
+                version: 0,
+                reason: "expected a list of counters",
+            })
+        );
+        assert_eq!(
+            from_v1(json!([])),
+            Err(MigrationError::Malformed {
+                version: 1,
+                reason: "expected an object",
+            })
+        );
+        assert_eq!(
+            migrate(json!({ "presets": [] })),
+            Err(MigrationError::Malformed {
+                version: 1,
+                reason: "expected a list of counters",
+            })
+        );
+        assert_eq!(
+            migrate(json!({ "counters": {} })),
+            Err(MigrationError::Malformed {
+                version: 1,
+                reason: "expected a list of counters",
+            })
+        );
+    }
+}
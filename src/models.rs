@@ -1,7 +1,24 @@
+use chrono::{DateTime, Utc};
 use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Sequence, Hash)]
+use crate::{color::Rgb, filter::normalize, icon::Icon, schedule::ResetSchedule, value::ValueKind};
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Sequence,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
 pub enum Color {
     #[default]
     System,
@@ -13,12 +30,310 @@ pub enum Color {
     Purple,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+impl Color {
+    /// Name of the matching Tailwind palette.
+    pub const fn palette_name(self) -> &'static str {
+        match self {
+            Self::System => "base",
+            Self::Red => "red",
+            Self::Orange => "orange",
+            Self::Yellow => "yellow",
+            Self::Green => "green",
+            Self::Blue => "blue",
+            Self::Purple => "purple",
+        }
+    }
+
+    /// The palette's 500 shade. The system color follows the theme and has none.
+    pub const fn rgb(self) -> Option<Rgb> {
+        match self {
+            Self::System => None,
+            Self::Red => Some(Rgb::new(0xef, 0x44, 0x44)),
+            Self::Orange => Some(Rgb::new(0xf9, 0x73, 0x16)),
+            Self::Yellow => Some(Rgb::new(0xea, 0xb3, 0x08)),
+            Self::Green => Some(Rgb::new(0x22, 0xc5, 0x5e)),
+            Self::Blue => Some(Rgb::new(0x3b, 0x82, 0xf6)),
+            Self::Purple => Some(Rgb::new(0xa8, 0x55, 0xf7)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum EventKind {
+    Increment,
+    Decrement,
+    Set,
+    Reset,
+    /// A value restored by undo. The log only grows, so undoing a change is
+    /// recorded rather than removing the entry it reverts.
+    Undo,
+    Redo,
+}
+
+/// A closed counting period, archived when a counter is reset.
+///
+/// `value` is the raw count the counter had when the session ended.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Session {
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: DateTime<Utc>,
+    pub value: i64,
+}
+
+/// A single change to a counter's value, kept in the counter's append-only log.
+///
+/// `delta` and `value` are raw values in the counter's [`ValueKind`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CounterEvent {
+    pub at: DateTime<Utc>,
+    pub kind: EventKind,
+    pub delta: i64,
+    pub value: i64,
+}
+
+/// Optional bounds on a counter's value and a target to count towards.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Limits {
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    pub goal: Option<i64>,
+}
+
+impl Limits {
+    pub const fn is_valid(&self) -> bool {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => min <= max,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Counter {
     pub id: Uuid,
     pub title: String,
-    pub count: usize,
+    /// Raw value, interpreted according to `kind`.
+    pub count: i64,
+    /// Named color, or the one nearest to `custom_color` when that is set.
     pub color: Color,
+    /// User-picked color shown instead of the named one.
+    #[serde(default)]
+    pub custom_color: Option<Rgb>,
+    /// Changes not yet handed to the store, which keeps the whole log. A
+    /// saved document holds the whole log here instead.
+    #[serde(default)]
+    pub events: Vec<CounterEvent>,
+    /// Amount added or removed by the increment and decrement buttons.
+    #[serde(default = "default_step")]
+    pub step: i64,
+    #[serde(default)]
+    pub limits: Limits,
+    #[serde(default)]
+    pub kind: ValueKind,
+    /// Raw value the counter returns to when reset.
+    #[serde(default)]
+    pub reset_value: i64,
+    /// Sessions not yet handed to the store, like `events`.
+    #[serde(default)]
+    pub sessions: Vec<Session>,
+    #[serde(default)]
+    pub schedule: Option<ResetSchedule>,
+    /// When any part of the counter was last edited.
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// The [`Group`] the counter is listed under, if any.
+    #[serde(default)]
+    pub group: Option<Uuid>,
+    /// Free-form labels, kept as typed.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Longer description, written in Markdown.
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub icon: Option<Icon>,
+    /// When the value first changed, where the first session starts.
+    #[serde(skip)]
+    pub first_change: Option<DateTime<Utc>>,
+    /// When the last session ended.
+    #[serde(skip)]
+    pub last_reset: Option<DateTime<Utc>>,
+}
+
+const fn default_step() -> i64 {
+    1
+}
+
+impl Counter {
+    pub fn lower_bound(&self) -> i64 {
+        let floor = self.kind.floor();
+        self.limits.min.map_or(floor, |min| min.max(floor))
+    }
+
+    pub fn upper_bound(&self) -> i64 {
+        self.limits.max.unwrap_or(i64::MAX)
+    }
+
+    /// Sets the count, kept within the counter's limits, and logs the change.
+    /// Changes that leave the count as it was are not logged.
+    ///
+    /// A count left outside the limits when they were changed may move back
+    /// towards them, but no further out.
+    pub fn with_count(self, count: i64, kind: EventKind) -> Self {
+        let count = self.bounded(count);
+        if count == self.count {
+            return self;
+        }
+
+        self.logged(count, kind)
+    }
+
+    /// `count` kept within the limits, as [`Counter::with_count`] describes.
+    fn bounded(&self, count: i64) -> i64 {
+        let lower = self.lower_bound().min(self.count).max(self.kind.floor());
+        let upper = self.upper_bound().max(self.count);
+        count.clamp(lower, upper)
+    }
+
+    /// Sets the count and logs the change, even when there is none.
+    fn logged(mut self, count: i64, kind: EventKind) -> Self {
+        let at = Utc::now();
+        self.first_change.get_or_insert(at);
+        self.events.push(CounterEvent {
+            at,
+            kind,
+            delta: count.saturating_sub(self.count),
+            value: count,
+        });
+        self.count = count;
+        self
+    }
+
+    /// Replaces the limits, which apply from the next change of value. The
+    /// count is left as it is, so typing a limit one digit at a time never
+    /// clamps it. Inconsistent limits (a minimum above the maximum) are ignored.
+    pub const fn with_limits(mut self, limits: Limits) -> Self {
+        if limits.is_valid() {
+            self.limits = limits;
+        }
+        self
+    }
+
+    /// Switches the value kind, converting the count, step, limits and the
+    /// unsaved changes into the new representation. The store converts the
+    /// rest of the history when it is saved.
+    pub fn with_kind(mut self, kind: ValueKind) -> Self {
+        let from = self.kind;
+        let rescale = |raw: i64| from.rescale(raw, kind);
+
+        self.kind = kind;
+        self.count = rescale(self.count);
+        self.step = rescale(self.step).max(1);
+        self.limits = Limits {
+            min: self.limits.min.map(|min| rescale(min).max(kind.floor())),
+            max: self.limits.max.map(|max| rescale(max).max(kind.floor())),
+            goal: self.limits.goal.map(rescale),
+        };
+        self.reset_value = rescale(self.reset_value).max(kind.floor());
+        self.rescale_log(from, kind);
+
+        let count = self.count;
+        self.with_count(count, EventKind::Set)
+    }
+
+    /// Returns to `snapshot`, an earlier state of this counter, keeping the
+    /// logs as they are now. A change of value is logged as `kind`.
+    pub fn restore(self, snapshot: Self, kind: EventKind) -> Self {
+        let previous = self.kind.rescale(self.count, snapshot.kind);
+        let mut restored = Self {
+            events: self.events,
+            sessions: self.sessions,
+            first_change: self.first_change,
+            last_reset: self.last_reset,
+            ..snapshot
+        };
+        restored.rescale_log(self.kind, restored.kind);
+
+        if restored.count != previous {
+            restored.events.push(CounterEvent {
+                at: Utc::now(),
+                kind,
+                delta: restored.count.saturating_sub(previous),
+                value: restored.count,
+            });
+        }
+        restored
+    }
+
+    fn rescale_log(&mut self, from: ValueKind, to: ValueKind) {
+        rescale_log(&mut self.events, &mut self.sessions, from, to);
+    }
+
+    /// Archives the current count as a closed session and returns the counter
+    /// to its reset value.
+    pub fn reset(self) -> Self {
+        self.reset_at(Utc::now())
+    }
+
+    /// Like [`Counter::reset`], but closes the session at `ended_at`, such as
+    /// the time a scheduled reset was due.
+    pub fn reset_at(mut self, ended_at: DateTime<Utc>) -> Self {
+        self.sessions.push(Session {
+            started_at: self.last_reset.or(self.first_change),
+            ended_at,
+            value: self.count,
+        });
+        self.last_reset = Some(ended_at);
+
+        // Logged even when the count is already the reset value, so every
+        // session boundary has its reset in the log.
+        let count = self.bounded(self.reset_value);
+        self.logged(count, EventKind::Reset)
+    }
+
+    /// The counter without its unsaved log entries, which are returned
+    /// alongside it.
+    pub fn take_logs(mut self) -> (Self, Vec<CounterEvent>, Vec<Session>) {
+        let events = std::mem::take(&mut self.events);
+        let sessions = std::mem::take(&mut self.sessions);
+        (self, events, sessions)
+    }
+
+    /// Fills in `first_change` and `last_reset` from the logs, for a counter
+    /// read together with its whole history.
+    pub fn with_log_summary(self) -> Self {
+        Self {
+            first_change: self.events.first().map(|event| event.at),
+            last_reset: self.sessions.last().map(|session| session.ended_at),
+            ..self
+        }
+    }
+
+    pub fn goal_reached(&self) -> bool {
+        self.limits.goal.is_some_and(|goal| self.count >= goal)
+    }
+
+    /// Whether the counter carries `tag`, ignoring case and diacritics.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = normalize(tag);
+        self.tags.iter().any(|own| normalize(own) == tag)
+    }
+}
+
+/// Converts log entries recorded in `from` into raw values of `to`.
+pub fn rescale_log(
+    events: &mut [CounterEvent],
+    sessions: &mut [Session],
+    from: ValueKind,
+    to: ValueKind,
+) {
+    for event in events {
+        event.delta = from.rescale(event.delta, to);
+        event.value = from.rescale(event.value, to);
+    }
+    for session in sessions {
+        session.value = from.rescale(session.value, to);
+    }
 }
 
 impl Default for Counter {
@@ -28,6 +343,51 @@ impl Default for Counter {
             title: String::from("Untitled"),
             count: Default::default(),
             color: Color::default(),
+            custom_color: None,
+            events: Vec::new(),
+            step: default_step(),
+            limits: Limits::default(),
+            kind: ValueKind::default(),
+            reset_value: 0,
+            sessions: Vec::new(),
+            schedule: None,
+            updated_at: None,
+            group: None,
+            tags: Vec::new(),
+            notes: String::new(),
+            icon: None,
+            first_change: None,
+            last_reset: None,
+        }
+    }
+}
+
+/// A named combination of filter panel settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterPreset {
+    pub id: Uuid,
+    pub name: String,
+    pub colors: Vec<Color>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub text: String,
+}
+
+/// A named, collapsible section of the counter list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Group {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub collapsed: bool,
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: String::from("New group"),
+            collapsed: false,
         }
     }
 }
@@ -0,0 +1,594 @@
+use std::fmt;
+
+use enum_iterator::all;
+
+use crate::{
+    filter::normalize,
+    fuzzy::{fuzzy_match, Match},
+    models::{Color, Counter},
+    value::ValueKind,
+};
+
+/// A bare word matching a tag scores this many times less than the same
+/// match in the title.
+const TAG_WEIGHT: i64 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Term {
+    /// A bare word the title or a tag must contain, fuzzily unless negated,
+    /// or the notes must contain as written.
+    Text(String),
+    /// A quoted phrase the title or notes must contain as written, spaces included.
+    Phrase(String),
+    /// `title:value`, matched against the title only.
+    Title(String),
+    /// `notes:value`, matched against the notes only.
+    Notes(String),
+    /// `color:red`
+    Color(Color),
+    /// `tag:warehouse`
+    Tag(String),
+    /// `count>10`, with the number in [`ValueKind::FINEST`] units.
+    Count(Comparison, i64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clause {
+    pub negated: bool,
+    pub term: Term,
+}
+
+/// A parsed filter query. Every clause must match.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Query {
+    pub clauses: Vec<Clause>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    UnterminatedQuote,
+    MissingTerm,
+    MissingValue(String),
+    UnknownColor(String),
+    InvalidNumber(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// Character offset in the query where the problem starts.
+    pub position: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::UnterminatedQuote => write!(f, "missing closing quote"),
+            ParseErrorKind::MissingTerm => write!(f, "expected a term after `-`"),
+            ParseErrorKind::MissingValue(field) => write!(f, "expected a value after `{field}`"),
+            ParseErrorKind::UnknownColor(name) => write!(
+                f,
+                "unknown color `{name}`, expected one of {}",
+                all::<Color>()
+                    .map(Color::palette_name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ParseErrorKind::InvalidNumber(text) => write!(f, "`{text}` is not a number"),
+        }?;
+        write!(f, " (at character {})", self.position + 1)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ParseError {
+    const fn new(kind: ParseErrorKind, position: usize) -> Self {
+        Self { kind, position }
+    }
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        Parser {
+            chars: input.chars().collect(),
+            position: 0,
+        }
+        .query()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// Bare words that rank results: they match fuzzily and contribute to the score.
+    pub fn has_fuzzy_terms(&self) -> bool {
+        self.clauses
+            .iter()
+            .any(|clause| !clause.negated && matches!(clause.term, Term::Text(_)))
+    }
+
+    /// Evaluates every clause against `counter`. Bare words are matched
+    /// fuzzily against the title, or failing that the tags at a fraction of
+    /// the score. Their scores add up and the matched title characters are
+    /// reported for highlighting. A bare word found only in the notes still
+    /// matches, but adds nothing to the score.
+    pub fn evaluate(&self, counter: &Counter) -> Option<Match> {
+        let mut result = Match::default();
+        for clause in &self.clauses {
+            match &clause.term {
+                Term::Text(text) if !clause.negated => {
+                    let found = fuzzy_match(text, &counter.title).or_else(|| {
+                        counter
+                            .tags
+                            .iter()
+                            .filter_map(|tag| fuzzy_match(text, tag))
+                            .map(|found| found.score / TAG_WEIGHT)
+                            .max()
+                            .map(|score| Match {
+                                score,
+                                positions: Vec::new(),
+                            })
+                    });
+                    match found {
+                        Some(found) => {
+                            result.score += found.score;
+                            result.positions.extend(found.positions);
+                        }
+                        None if contains(&counter.notes, text) => {}
+                        None => return None,
+                    }
+                }
+                term => {
+                    if term.matches(counter) == clause.negated {
+                        return None;
+                    }
+                }
+            }
+        }
+
+        result.positions.sort_unstable();
+        result.positions.dedup();
+        Some(result)
+    }
+}
+
+impl Term {
+    fn matches(&self, counter: &Counter) -> bool {
+        match self {
+            Self::Text(text) => {
+                contains(&counter.title, text)
+                    || contains(&counter.notes, text)
+                    || counter.tags.iter().any(|tag| contains(tag, text))
+            }
+            Self::Phrase(text) => contains(&counter.title, text) || contains(&counter.notes, text),
+            Self::Title(text) => contains(&counter.title, text),
+            Self::Notes(text) => contains(&counter.notes, text),
+            Self::Color(color) => counter.color == *color,
+            Self::Tag(tag) => counter.has_tag(tag),
+            Self::Count(comparison, value) => {
+                let count = counter.kind.rescale(counter.count, ValueKind::FINEST);
+                match comparison {
+                    Comparison::Less => count < *value,
+                    Comparison::LessOrEqual => count <= *value,
+                    Comparison::Equal => count == *value,
+                    Comparison::GreaterOrEqual => count >= *value,
+                    Comparison::Greater => count > *value,
+                }
+            }
+        }
+    }
+}
+
+/// Whether `haystack` contains `needle`, ignoring case and diacritics.
+fn contains(haystack: &str, needle: &str) -> bool {
+    normalize(haystack).contains(&normalize(needle))
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let matched = self.peek() == Some(c);
+        if matched {
+            self.position += 1;
+        }
+        matched
+    }
+
+    fn at_term_end(&self) -> bool {
+        self.peek().is_none_or(char::is_whitespace)
+    }
+
+    fn query(mut self) -> Result<Query, ParseError> {
+        let mut clauses = Vec::new();
+        loop {
+            while self.peek().is_some_and(char::is_whitespace) {
+                self.position += 1;
+            }
+            if self.peek().is_none() {
+                return Ok(Query { clauses });
+            }
+            clauses.push(self.clause()?);
+        }
+    }
+
+    fn clause(&mut self) -> Result<Clause, ParseError> {
+        let start = self.position;
+        let negated = self.eat('-');
+        if negated && self.at_term_end() {
+            return Err(ParseError::new(ParseErrorKind::MissingTerm, start));
+        }
+
+        Ok(Clause {
+            negated,
+            term: self.term()?,
+        })
+    }
+
+    fn term(&mut self) -> Result<Term, ParseError> {
+        if self.peek() == Some('"') {
+            return self.quoted().map(Term::Phrase);
+        }
+
+        let start = self.position;
+        let name = self.take_while(char::is_alphabetic).to_lowercase();
+
+        match (name.as_str(), self.peek()) {
+            ("color" | "notes" | "tag" | "title", Some(':')) => {
+                self.position += 1;
+                let value_start = self.position;
+                let value = self.value()?;
+                if value.is_empty() {
+                    return Err(ParseError::new(
+                        ParseErrorKind::MissingValue(format!("{name}:")),
+                        start,
+                    ));
+                }
+
+                Ok(match name.as_str() {
+                    "color" => Term::Color(
+                        all::<Color>()
+                            .find(|color| color.palette_name().eq_ignore_ascii_case(&value))
+                            // The system color's earlier name, still found in saved presets.
+                            .or_else(|| {
+                                value
+                                    .eq_ignore_ascii_case("default")
+                                    .then_some(Color::System)
+                            })
+                            .ok_or_else(|| {
+                                ParseError::new(
+                                    ParseErrorKind::UnknownColor(value.clone()),
+                                    value_start,
+                                )
+                            })?,
+                    ),
+                    "notes" => Term::Notes(value),
+                    "tag" => Term::Tag(value),
+                    _ => Term::Title(value),
+                })
+            }
+            ("count", Some('<' | '>' | '=')) => {
+                let comparison = if self.eat('<') {
+                    if self.eat('=') {
+                        Comparison::LessOrEqual
+                    } else {
+                        Comparison::Less
+                    }
+                } else if self.eat('>') {
+                    if self.eat('=') {
+                        Comparison::GreaterOrEqual
+                    } else {
+                        Comparison::Greater
+                    }
+                } else {
+                    self.eat('=');
+                    Comparison::Equal
+                };
+
+                let value_start = self.position;
+                let text = self.take_while(|c| !c.is_whitespace());
+                if text.is_empty() {
+                    return Err(ParseError::new(
+                        ParseErrorKind::MissingValue(String::from("count")),
+                        start,
+                    ));
+                }
+                let value = ValueKind::FINEST.parse(&text).map_err(|_| {
+                    ParseError::new(ParseErrorKind::InvalidNumber(text), value_start)
+                })?;
+
+                Ok(Term::Count(comparison, value))
+            }
+            _ => {
+                // Not a known field: the whole run of text is a plain word.
+                self.position = start;
+                Ok(Term::Text(self.take_while(|c| !c.is_whitespace())))
+            }
+        }
+    }
+
+    /// A field value, optionally quoted to include spaces.
+    fn value(&mut self) -> Result<String, ParseError> {
+        if self.peek() == Some('"') {
+            self.quoted()
+        } else {
+            Ok(self.take_while(|c| !c.is_whitespace()))
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, ParseError> {
+        let start = self.position;
+        self.position += 1;
+        let text = self.take_while(|c| c != '"');
+        if !self.eat('"') {
+            return Err(ParseError::new(ParseErrorKind::UnterminatedQuote, start));
+        }
+        Ok(text)
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(&predicate) {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(input: &str) -> Vec<(bool, Term)> {
+        Query::parse(input)
+            .unwrap()
+            .clauses
+            .into_iter()
+            .map(|clause| (clause.negated, clause.term))
+            .collect()
+    }
+
+    fn error(input: &str) -> (ParseErrorKind, usize) {
+        let err = Query::parse(input).unwrap_err();
+        (err.kind, err.position)
+    }
+
+    fn counter(title: &str, notes: &str) -> Counter {
+        Counter {
+            title: String::from(title),
+            notes: String::from(notes),
+            ..Counter::default()
+        }
+    }
+
+    fn matches(input: &str, counter: &Counter) -> bool {
+        Query::parse(input).unwrap().evaluate(counter).is_some()
+    }
+
+    #[test]
+    fn parses_words_and_phrases() {
+        assert_eq!(
+            terms("  milk \"exact  phrase\" oat-milk "),
+            [
+                (false, Term::Text(String::from("milk"))),
+                (false, Term::Phrase(String::from("exact  phrase"))),
+                (false, Term::Text(String::from("oat-milk"))),
+            ]
+        );
+        assert_eq!(terms(""), []);
+        assert_eq!(terms("\"\""), [(false, Term::Phrase(String::new()))]);
+    }
+
+    #[test]
+    fn parses_field_prefixes() {
+        assert_eq!(
+            terms("title:milk notes:\"two words\" Color:Red TAG:warehouse"),
+            [
+                (false, Term::Title(String::from("milk"))),
+                (false, Term::Notes(String::from("two words"))),
+                (false, Term::Color(Color::Red)),
+                (false, Term::Tag(String::from("warehouse"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_plain_words() {
+        assert_eq!(
+            terms("size:large count http://example.com"),
+            [
+                (false, Term::Text(String::from("size:large"))),
+                (false, Term::Text(String::from("count"))),
+                (false, Term::Text(String::from("http://example.com"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_count_comparisons() {
+        let unit = ValueKind::FINEST.unit();
+        assert_eq!(
+            terms("count<1 count<=2 count=3 count>=4 count>5 count>1.5 count=-2"),
+            [
+                (false, Term::Count(Comparison::Less, unit)),
+                (false, Term::Count(Comparison::LessOrEqual, 2 * unit)),
+                (false, Term::Count(Comparison::Equal, 3 * unit)),
+                (false, Term::Count(Comparison::GreaterOrEqual, 4 * unit)),
+                (false, Term::Count(Comparison::Greater, 5 * unit)),
+                (false, Term::Count(Comparison::Greater, unit * 3 / 2)),
+                (false, Term::Count(Comparison::Equal, -2 * unit)),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_negation() {
+        assert_eq!(
+            terms("-milk -\"a b\" -color:red -count>3"),
+            [
+                (true, Term::Text(String::from("milk"))),
+                (true, Term::Phrase(String::from("a b"))),
+                (true, Term::Color(Color::Red)),
+                (
+                    true,
+                    Term::Count(Comparison::Greater, 3 * ValueKind::FINEST.unit())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_malformed_input() {
+        assert_eq!(error("\"open"), (ParseErrorKind::UnterminatedQuote, 0));
+        assert_eq!(
+            error("title:\"open"),
+            (ParseErrorKind::UnterminatedQuote, 6)
+        );
+        assert_eq!(error("milk -"), (ParseErrorKind::MissingTerm, 5));
+        assert_eq!(error("- milk"), (ParseErrorKind::MissingTerm, 0));
+        assert_eq!(
+            error("tag: milk"),
+            (ParseErrorKind::MissingValue(String::from("tag:")), 0)
+        );
+        assert_eq!(
+            error("x count>"),
+            (ParseErrorKind::MissingValue(String::from("count")), 2)
+        );
+        assert_eq!(
+            error("color:pink"),
+            (ParseErrorKind::UnknownColor(String::from("pink")), 6)
+        );
+        assert_eq!(
+            error("count>=ten"),
+            (ParseErrorKind::InvalidNumber(String::from("ten")), 7)
+        );
+        assert_eq!(
+            error("count>1.0000001"),
+            (ParseErrorKind::InvalidNumber(String::from("1.0000001")), 6)
+        );
+    }
+
+    #[test]
+    fn error_messages_point_at_the_problem() {
+        assert_eq!(
+            Query::parse("milk \"open").unwrap_err().to_string(),
+            "missing closing quote (at character 6)"
+        );
+        assert_eq!(
+            Query::parse("count>x").unwrap_err().to_string(),
+            "`x` is not a number (at character 7)"
+        );
+    }
+
+    #[test]
+    fn bare_words_match_titles_fuzzily() {
+        let milk = counter("Milk bottles", "");
+        let found = Query::parse("mlk").unwrap().evaluate(&milk).unwrap();
+        assert!(found.score > 0);
+        assert_eq!(found.positions, [0, 2, 3]);
+
+        assert!(!matches("xyz", &milk));
+        // Phrases and fields are matched as written.
+        assert!(!matches("\"mlk\"", &milk));
+        assert!(!matches("title:mlk", &milk));
+        assert!(matches("title:bottle", &milk));
+    }
+
+    #[test]
+    fn negated_words_are_not_fuzzy() {
+        let milk = counter("Milk bottles", "");
+        assert!(matches("-mlk", &milk));
+        assert!(!matches("-milk", &milk));
+        assert!(!matches("-bottles", &milk));
+    }
+
+    #[test]
+    fn scores_add_up_and_fields_only_filter() {
+        let milk = counter("Milk bottles", "");
+        let score = |input: &str| Query::parse(input).unwrap().evaluate(&milk).unwrap().score;
+
+        assert!(score("milk bottles") > score("milk"));
+        assert_eq!(score("color:base"), 0);
+        assert_eq!(score("color:default"), 0);
+        assert_eq!(score("milk -tag:dairy"), score("milk"));
+
+        let found = Query::parse("milk bot").unwrap().evaluate(&milk).unwrap();
+        assert_eq!(found.positions, [0, 1, 2, 3, 5, 6, 7]);
+    }
+
+    #[test]
+    fn words_in_tags_rank_below_titles() {
+        let tagged = Counter {
+            tags: vec![String::from("Warehouse")],
+            ..counter("Pallets", "")
+        };
+        let titled = counter("Warehouse pallets", "");
+        let score = |counter: &Counter| {
+            Query::parse("warehouse")
+                .unwrap()
+                .evaluate(counter)
+                .unwrap()
+                .score
+        };
+
+        assert!(score(&tagged) > 0);
+        assert!(score(&tagged) < score(&titled));
+        assert!(matches("wrehouse", &tagged));
+        assert!(!matches("-warehouse", &tagged));
+    }
+
+    #[test]
+    fn words_in_notes_match_without_score() {
+        let pallets = counter("Pallets", "Stored in the warehouse");
+        let found = Query::parse("warehouse")
+            .unwrap()
+            .evaluate(&pallets)
+            .unwrap();
+        assert_eq!(found, Match::default());
+
+        assert!(matches("notes:stored", &pallets));
+        assert!(!matches("notes:pallets", &pallets));
+        assert!(matches("\"the warehouse\"", &pallets));
+        assert!(!matches("-warehouse", &pallets));
+    }
+
+    #[test]
+    fn matches_colors_tags_and_counts() {
+        let fuel = Counter {
+            color: Color::Red,
+            tags: vec![String::from("Café")],
+            kind: ValueKind::Decimal { places: 2 },
+            count: 150,
+            ..counter("Fuel", "")
+        };
+
+        assert!(matches("color:red", &fuel));
+        assert!(!matches("color:blue", &fuel));
+        assert!(matches("tag:cafe", &fuel));
+        assert!(!matches("-tag:CAFÉ", &fuel));
+        assert!(matches(
+            "count>1.4 count>=1.5 count=1.5 count<=1.5 count<2",
+            &fuel
+        ));
+        assert!(!matches("count>1.5", &fuel));
+        assert!(!matches("count<1.5", &fuel));
+        assert!(matches("-count<1", &fuel));
+    }
+}
@@ -0,0 +1,316 @@
+use chrono::{
+    DateTime, Datelike, Days, Duration, Months, NaiveDate, NaiveTime, TimeZone, Utc, Weekday,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::models::Counter;
+
+/// Longest stretch of local time a daylight saving change skips.
+const MAX_GAP_MINUTES: i64 = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Repeat {
+    Daily,
+    /// Days from Monday, so `0` is Monday and `6` is Sunday.
+    Weekly {
+        weekday: u8,
+    },
+    /// Day of the month. Months shorter than `day` reset on their last day.
+    Monthly {
+        day: u32,
+    },
+}
+
+/// When a counter resets itself, in local time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ResetSchedule {
+    pub repeat: Repeat,
+    pub at: NaiveTime,
+    /// When the schedule was set up. Occurrences before this are not applied.
+    pub since: DateTime<Utc>,
+}
+
+impl Repeat {
+    pub fn weekday(weekday: u8) -> Weekday {
+        Weekday::try_from(weekday).unwrap_or(Weekday::Mon)
+    }
+}
+
+impl ResetSchedule {
+    pub fn new(repeat: Repeat, at: NaiveTime) -> Self {
+        Self {
+            repeat,
+            at,
+            since: Utc::now(),
+        }
+    }
+
+    /// The latest scheduled reset at or before `now`, in the time zone of `now`.
+    pub fn previous_occurrence<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> Option<DateTime<Utc>> {
+        let zone = now.timezone();
+        let today = now.date_naive();
+        let date = match self.repeat {
+            Repeat::Daily => {
+                let candidate = today;
+                if self.local(&zone, candidate)? > *now {
+                    candidate.checked_sub_days(Days::new(1))?
+                } else {
+                    candidate
+                }
+            }
+            Repeat::Weekly { weekday } => {
+                let target = Repeat::weekday(weekday).num_days_from_monday();
+                let current = today.weekday().num_days_from_monday();
+                let candidate =
+                    today.checked_sub_days(Days::new(u64::from((current + 7 - target) % 7)))?;
+                if self.local(&zone, candidate)? > *now {
+                    candidate.checked_sub_days(Days::new(7))?
+                } else {
+                    candidate
+                }
+            }
+            Repeat::Monthly { day } => {
+                let candidate = day_in_month(today, day)?;
+                if self.local(&zone, candidate)? > *now {
+                    day_in_month(today.checked_sub_months(Months::new(1))?, day)?
+                } else {
+                    candidate
+                }
+            }
+        };
+
+        self.local(&zone, date).map(|at| at.with_timezone(&Utc))
+    }
+
+    /// `self.at` on `date` in `zone`. A time skipped by a daylight saving change is
+    /// moved to the first minute after the gap, so the day still resets.
+    fn local<Tz: TimeZone>(&self, zone: &Tz, date: NaiveDate) -> Option<DateTime<Tz>> {
+        let at = date.and_time(self.at);
+        (0..=MAX_GAP_MINUTES).find_map(|minutes| {
+            zone.from_local_datetime(&(at + Duration::minutes(minutes)))
+                .earliest()
+        })
+    }
+}
+
+/// `day` of the month containing `date`, clamped to the month's last day.
+fn day_in_month(date: NaiveDate, day: u32) -> Option<NaiveDate> {
+    let first = date.with_day(1)?;
+    let last = first
+        .checked_add_months(Months::new(1))?
+        .checked_sub_days(Days::new(1))?;
+    first.with_day(day.clamp(1, last.day()))
+}
+
+/// The scheduled reset that is due for `counter`, if it has not yet been
+/// reset since that time.
+pub fn due_reset<Tz: TimeZone>(counter: &Counter, now: &DateTime<Tz>) -> Option<DateTime<Utc>> {
+    let schedule = counter.schedule?;
+    let occurrence = schedule.previous_occurrence(now)?;
+    let last_reset = counter
+        .last_reset
+        .map_or(schedule.since, |ended_at| ended_at.max(schedule.since));
+
+    (occurrence > last_reset).then_some(occurrence)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, MappedLocalTime, NaiveDateTime};
+
+    use super::*;
+
+    /// Central European time around the start of summer time in 2026, when
+    /// clocks jump from 02:00 to 03:00 on 29 March.
+    #[derive(Debug, Clone, Copy)]
+    struct Berlin;
+
+    impl Berlin {
+        fn winter() -> FixedOffset {
+            FixedOffset::east_opt(3600).unwrap()
+        }
+
+        fn summer() -> FixedOffset {
+            FixedOffset::east_opt(7200).unwrap()
+        }
+    }
+
+    impl TimeZone for Berlin {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Self
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_local_datetime(
+            &self,
+            local: &NaiveDateTime,
+        ) -> MappedLocalTime<FixedOffset> {
+            let gap = moment(2026, 3, 29, 2, 0);
+            if *local < gap {
+                MappedLocalTime::Single(Self::winter())
+            } else if *local < gap + Duration::hours(1) {
+                MappedLocalTime::None
+            } else {
+                MappedLocalTime::Single(Self::summer())
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            if *utc < moment(2026, 3, 29, 1, 0) {
+                Self::winter()
+            } else {
+                Self::summer()
+            }
+        }
+    }
+
+    fn moment(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&moment(year, month, day, hour, minute))
+    }
+
+    fn schedule(repeat: Repeat, hour: u32) -> ResetSchedule {
+        ResetSchedule {
+            repeat,
+            at: NaiveTime::from_hms_opt(hour, 0, 0).unwrap(),
+            since: utc(2020, 1, 1, 0, 0),
+        }
+    }
+
+    #[test]
+    fn a_time_skipped_by_summer_time_resets_after_the_gap() {
+        let daily = ResetSchedule {
+            at: NaiveTime::from_hms_opt(2, 30, 0).unwrap(),
+            ..schedule(Repeat::Daily, 0)
+        };
+        let now = Berlin
+            .from_local_datetime(&moment(2026, 3, 29, 12, 0))
+            .unwrap();
+
+        // 03:00 in summer time, the first minute after the gap.
+        assert_eq!(
+            daily.previous_occurrence(&now),
+            Some(utc(2026, 3, 29, 1, 0))
+        );
+        // The day before is untouched: 02:30 in winter time.
+        let before = Berlin
+            .from_local_datetime(&moment(2026, 3, 29, 1, 0))
+            .unwrap();
+        assert_eq!(
+            daily.previous_occurrence(&before),
+            Some(utc(2026, 3, 28, 1, 30))
+        );
+    }
+
+    #[test]
+    fn daily_resets_wait_for_the_time_of_day() {
+        let daily = schedule(Repeat::Daily, 8);
+
+        assert_eq!(
+            daily.previous_occurrence(&utc(2026, 3, 2, 7, 59)),
+            Some(utc(2026, 3, 1, 8, 0))
+        );
+        assert_eq!(
+            daily.previous_occurrence(&utc(2026, 3, 2, 8, 0)),
+            Some(utc(2026, 3, 2, 8, 0))
+        );
+    }
+
+    #[test]
+    fn weekly_resets_wrap_around_the_week() {
+        // 2 March 2026 is a Monday.
+        let mondays = schedule(Repeat::Weekly { weekday: 0 }, 8);
+        let sundays = schedule(Repeat::Weekly { weekday: 6 }, 8);
+
+        assert_eq!(
+            mondays.previous_occurrence(&utc(2026, 3, 2, 7, 0)),
+            Some(utc(2026, 2, 23, 8, 0))
+        );
+        assert_eq!(
+            mondays.previous_occurrence(&utc(2026, 3, 2, 9, 0)),
+            Some(utc(2026, 3, 2, 8, 0))
+        );
+        assert_eq!(
+            sundays.previous_occurrence(&utc(2026, 3, 2, 7, 0)),
+            Some(utc(2026, 3, 1, 8, 0))
+        );
+        assert_eq!(
+            sundays.previous_occurrence(&utc(2026, 3, 7, 23, 0)),
+            Some(utc(2026, 3, 1, 8, 0))
+        );
+    }
+
+    #[test]
+    fn monthly_resets_move_to_the_last_day_of_short_months() {
+        let last_day = schedule(Repeat::Monthly { day: 31 }, 8);
+
+        assert_eq!(
+            last_day.previous_occurrence(&utc(2026, 2, 28, 9, 0)),
+            Some(utc(2026, 2, 28, 8, 0))
+        );
+        assert_eq!(
+            last_day.previous_occurrence(&utc(2026, 2, 27, 9, 0)),
+            Some(utc(2026, 1, 31, 8, 0))
+        );
+        assert_eq!(
+            last_day.previous_occurrence(&utc(2026, 3, 10, 9, 0)),
+            Some(utc(2026, 2, 28, 8, 0))
+        );
+        // Leap years reset on the 29th instead.
+        assert_eq!(
+            last_day.previous_occurrence(&utc(2028, 2, 28, 9, 0)),
+            Some(utc(2028, 1, 31, 8, 0))
+        );
+        assert_eq!(
+            last_day.previous_occurrence(&utc(2028, 2, 29, 9, 0)),
+            Some(utc(2028, 2, 29, 8, 0))
+        );
+        assert_eq!(
+            last_day.previous_occurrence(&utc(2028, 3, 10, 9, 0)),
+            Some(utc(2028, 2, 29, 8, 0))
+        );
+    }
+
+    #[test]
+    fn a_due_reset_fires_once() {
+        let counter = Counter {
+            count: 5,
+            schedule: Some(ResetSchedule {
+                since: utc(2026, 3, 1, 12, 0),
+                ..schedule(Repeat::Daily, 8)
+            }),
+            ..Counter::default()
+        };
+
+        // The schedule was set up after that morning's reset time.
+        assert_eq!(due_reset(&counter, &utc(2026, 3, 1, 20, 0)), None);
+
+        let now = utc(2026, 3, 2, 9, 0);
+        let due = due_reset(&counter, &now);
+        assert_eq!(due, Some(utc(2026, 3, 2, 8, 0)));
+
+        let counter = counter.reset_at(due.unwrap());
+        assert_eq!(counter.count, 0);
+        assert_eq!(due_reset(&counter, &now), None);
+        assert_eq!(due_reset(&counter, &utc(2026, 3, 2, 23, 0)), None);
+        assert_eq!(
+            due_reset(&counter, &utc(2026, 3, 3, 8, 0)),
+            Some(utc(2026, 3, 3, 8, 0))
+        );
+    }
+}
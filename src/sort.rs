@@ -0,0 +1,59 @@
+use std::cmp::Reverse;
+
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+
+use crate::{filter::normalize, models::Counter, value::ValueKind};
+
+/// How the list is ordered for display. Only `Manual` shows the saved order,
+/// which the other modes leave untouched.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Sequence,
+    Serialize,
+    Deserialize,
+)]
+pub enum SortMode {
+    #[default]
+    Manual,
+    Title,
+    CountAscending,
+    CountDescending,
+    Color,
+    RecentlyChanged,
+}
+
+impl SortMode {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Manual => "Manual",
+            Self::Title => "Title A–Z",
+            Self::CountAscending => "Count ascending",
+            Self::CountDescending => "Count descending",
+            Self::Color => "Color",
+            Self::RecentlyChanged => "Recently changed",
+        }
+    }
+
+    /// Sorts `counters` in place. Ties keep their existing relative order.
+    pub fn sort(self, counters: &mut [Counter]) {
+        let count = |counter: &Counter| counter.kind.rescale(counter.count, ValueKind::FINEST);
+
+        match self {
+            Self::Manual => {}
+            Self::Title => counters.sort_by_cached_key(|counter| normalize(&counter.title)),
+            Self::CountAscending => counters.sort_by_key(count),
+            Self::CountDescending => counters.sort_by_key(|counter| Reverse(count(counter))),
+            Self::Color => counters.sort_by_key(|counter| counter.color),
+            Self::RecentlyChanged => counters.sort_by_key(|counter| Reverse(counter.updated_at)),
+        }
+    }
+}
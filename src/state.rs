@@ -0,0 +1,305 @@
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use chrono::{DateTime, Local, Utc};
+use dioxus::prelude::*;
+use dioxus_logger::tracing::error;
+use uuid::Uuid;
+
+use crate::{
+    history::{Coalesce, History},
+    models::{self, Color, Counter, EventKind, FilterPreset, Group},
+    schedule,
+    sort::SortMode,
+    storage::{self, Store, Unsaved},
+};
+
+/// State shared by every page. It is created in `App`, above the router, so
+/// the list keeps its filter while a counter's detail page is open.
+#[derive(Clone, Copy)]
+pub struct AppState {
+    /// The counters without their history, which is kept by the store.
+    pub counters: Signal<Vec<Counter>>,
+    /// Log entries waiting for the next save.
+    pub unsaved: Signal<Vec<Unsaved>>,
+    /// Bumped after every save, so views reading history from the store
+    /// read it again.
+    pub revision: Signal<u64>,
+    pub store: CopyValue<Rc<dyn Store>>,
+    /// Undo steps hold no history, except for counters deleted by the step
+    /// after them, which bring theirs back when the deletion is undone.
+    pub history: Signal<History<Vec<Counter>>>,
+    pub presets: Signal<Vec<FilterPreset>>,
+    pub groups: Signal<Vec<Group>>,
+    pub sort_mode: Signal<SortMode>,
+    pub load_error: Signal<Option<String>>,
+    /// Whether the data failed to load because a newer build saved it, so it
+    /// must be neither overwritten nor moved aside.
+    pub newer_data: Signal<bool>,
+    pub save_error: Signal<Option<String>>,
+    pub show_filter: Signal<bool>,
+    pub filter_colors: Signal<HashMap<Color, bool>>,
+    pub filter_text: Signal<String>,
+    pub filter_tags: Signal<Vec<String>>,
+    /// Vertical scroll offset of the list, restored when returning to it.
+    pub list_scroll: Signal<f64>,
+}
+
+impl AppState {
+    /// Every mutation goes through here so it can be undone. Counters that
+    /// differ from the current ones are stamped with the time of the change.
+    pub fn commit(mut self, next: Vec<Counter>, coalesce: Option<Coalesce>) {
+        let previous = (self.counters)();
+        let next = stamp(&previous, next);
+        // An edit that changes nothing, like incrementing at the maximum,
+        // would leave an undo step that seems to do nothing.
+        if next == previous {
+            return;
+        }
+        let previous = self.keep_history(previous, &ids(&next));
+
+        self.history.write().record(previous, coalesce);
+        self.set_counters(next);
+    }
+
+    /// Applies the scheduled resets due at `now`. They happen whether or not
+    /// earlier changes are undone, so they are applied to every undo and redo
+    /// step too rather than being recorded as one.
+    ///
+    /// The sessions they close are logged once, by the current counters.
+    /// Counters deleted since an undo step are left as they were, and reset
+    /// if the deletion is undone.
+    pub fn apply_due_resets(mut self, now: DateTime<Local>) {
+        let reset = |counter: Counter| match schedule::due_reset(&counter, &now) {
+            Some(due) => counter.reset_at(due),
+            None => counter,
+        };
+
+        let previous = (self.counters)();
+        if previous
+            .iter()
+            .all(|counter| schedule::due_reset(counter, &now).is_none())
+        {
+            return;
+        }
+
+        let live = ids(&previous);
+        self.history.write().rewrite(|counters| {
+            *counters = std::mem::take(counters)
+                .into_iter()
+                .map(|counter| {
+                    if live.contains(&counter.id) {
+                        reset(counter).take_logs().0
+                    } else {
+                        counter
+                    }
+                })
+                .collect();
+        });
+        let next = stamp(&previous, previous.clone().into_iter().map(reset).collect());
+        self.set_counters(next);
+    }
+
+    /// Removes the group with `id`, leaving its counters ungrouped. Groups are
+    /// not part of the undo history, so this can't be undone, and the counters
+    /// are ungrouped in every undo and redo step too rather than pointing at a
+    /// group that is gone.
+    pub fn delete_group(mut self, id: Uuid) {
+        let ungroup = |counters: &mut Vec<Counter>| {
+            for counter in counters
+                .iter_mut()
+                .filter(|counter| counter.group == Some(id))
+            {
+                counter.group = None;
+            }
+        };
+
+        self.groups
+            .with_mut(|groups| groups.retain(|group| group.id != id));
+        self.history.write().rewrite(ungroup);
+        let previous = (self.counters)();
+        let mut next = previous.clone();
+        ungroup(&mut next);
+        self.set_counters(stamp(&previous, next));
+    }
+
+    /// Commits `update` applied to the counter with `id`.
+    pub fn update(self, id: Uuid, coalesce: Option<Coalesce>, update: impl Fn(Counter) -> Counter) {
+        let next = (self.counters)()
+            .into_iter()
+            .map(|counter| {
+                if counter.id == id {
+                    update(counter)
+                } else {
+                    counter
+                }
+            })
+            .collect();
+
+        self.commit(next, coalesce);
+    }
+
+    /// Returns to the counters before the last change. Logs are kept, so the
+    /// value each counter returns to is logged instead of erasing entries.
+    pub fn undo(mut self) {
+        let current = (self.counters)();
+        let Some(kept) = self
+            .history
+            .peek()
+            .next_undo()
+            .map(|previous| ids(previous))
+        else {
+            return;
+        };
+        let snapshot = self.keep_history(current.clone(), &kept);
+        let previous = self.history.write().undo(snapshot);
+        if let Some(previous) = previous {
+            self.set_counters(restore(current, previous, EventKind::Undo));
+        }
+    }
+
+    /// Reapplies the change most recently undone, logged like [`AppState::undo`].
+    pub fn redo(mut self) {
+        let current = (self.counters)();
+        let Some(kept) = self.history.peek().next_redo().map(|next| ids(next)) else {
+            return;
+        };
+        let snapshot = self.keep_history(current.clone(), &kept);
+        let next = self.history.write().redo(snapshot);
+        if let Some(next) = next {
+            self.set_counters(restore(current, next, EventKind::Redo));
+        }
+    }
+
+    /// Ctrl or Cmd with Z to undo, and with Shift+Z or Y to redo.
+    pub fn handle_shortcut(self, e: &KeyboardEvent) {
+        let modifiers = e.modifiers();
+        if !(modifiers.ctrl() || modifiers.meta()) {
+            return;
+        }
+
+        match e.key() {
+            Key::Character(key) if key.eq_ignore_ascii_case("z") && modifiers.shift() => {
+                e.prevent_default();
+                self.redo();
+            }
+            Key::Character(key) if key.eq_ignore_ascii_case("z") => {
+                e.prevent_default();
+                self.undo();
+            }
+            Key::Character(key) if key.eq_ignore_ascii_case("y") => {
+                e.prevent_default();
+                self.redo();
+            }
+            _ => {}
+        }
+    }
+
+    /// Sets the counters, moving the entries they logged to `unsaved`.
+    fn set_counters(mut self, counters: Vec<Counter>) {
+        let counters = counters
+            .into_iter()
+            .map(|counter| {
+                let (counter, events, sessions) = counter.take_logs();
+                if !events.is_empty() || !sessions.is_empty() {
+                    self.unsaved.write().push(Unsaved {
+                        counter: counter.id,
+                        kind: counter.kind,
+                        events,
+                        sessions,
+                    });
+                }
+                counter
+            })
+            .collect();
+
+        self.counters.set(counters);
+    }
+
+    /// `snapshot` with the whole history of the counters missing from `kept`,
+    /// which the store drops on the next save.
+    fn keep_history(self, snapshot: Vec<Counter>, kept: &HashSet<Uuid>) -> Vec<Counter> {
+        snapshot
+            .into_iter()
+            .map(|counter| {
+                if kept.contains(&counter.id) {
+                    counter
+                } else {
+                    self.with_history(counter)
+                }
+            })
+            .collect()
+    }
+
+    /// `counter` with its history from the store, followed by the entries
+    /// still waiting to be saved, which are taken from `unsaved`.
+    fn with_history(mut self, counter: Counter) -> Counter {
+        let store = self.store.cloned();
+        let mut counter = storage::with_history(&*store, counter.clone()).unwrap_or_else(|err| {
+            error!("failed to read the history of {}: {err}", counter.title);
+            counter
+        });
+
+        let (pending, rest) = std::mem::take(&mut *self.unsaved.write())
+            .into_iter()
+            .partition::<Vec<_>, _>(|batch| batch.counter == counter.id);
+        self.unsaved.set(rest);
+        for mut batch in pending {
+            models::rescale_log(
+                &mut batch.events,
+                &mut batch.sessions,
+                batch.kind,
+                counter.kind,
+            );
+            counter.events.extend(batch.events);
+            counter.sessions.extend(batch.sessions);
+        }
+
+        counter
+    }
+}
+
+fn ids(counters: &[Counter]) -> HashSet<Uuid> {
+    counters.iter().map(|counter| counter.id).collect()
+}
+
+/// `next` with the counters that differ from `previous` stamped with the
+/// time of the change.
+fn stamp(previous: &[Counter], next: Vec<Counter>) -> Vec<Counter> {
+    let now = Utc::now();
+    next.into_iter()
+        .map(|counter| {
+            let changed = previous
+                .iter()
+                .find(|old| old.id == counter.id)
+                .is_none_or(|old| *old != counter);
+            if changed {
+                Counter {
+                    updated_at: Some(now),
+                    ..counter
+                }
+            } else {
+                counter
+            }
+        })
+        .collect()
+}
+
+/// `snapshot` with the logs of the counters in `current`. Counters deleted
+/// since the snapshot come back with the logs they had then.
+fn restore(current: Vec<Counter>, snapshot: Vec<Counter>, kind: EventKind) -> Vec<Counter> {
+    let mut current = current
+        .into_iter()
+        .map(|counter| (counter.id, counter))
+        .collect::<HashMap<_, _>>();
+
+    snapshot
+        .into_iter()
+        .map(|counter| match current.remove(&counter.id) {
+            Some(now) => now.restore(counter, kind),
+            None => counter,
+        })
+        .collect()
+}
@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::{CounterEvent, EventKind, Session};
+
+/// Summary figures drawn from a counter's event log and sessions. Amounts are
+/// raw values in the counter's kind.
+///
+/// The figures are built up one entry at a time, so a long history can be
+/// summed as it is read rather than held in memory.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CounterStats {
+    pub events: usize,
+    pub increments: usize,
+    pub decrements: usize,
+    pub edits: usize,
+    pub resets: usize,
+    /// Sum of every increase, whatever caused it.
+    pub added: i64,
+    /// Sum of every decrease, as a positive amount.
+    pub removed: i64,
+    pub first_change: Option<DateTime<Utc>>,
+    pub last_change: Option<DateTime<Utc>>,
+    pub sessions: usize,
+    pub session_average: Option<i64>,
+    pub session_best: Option<i64>,
+    session_total: i64,
+}
+
+impl CounterStats {
+    /// Counts `event`. Events are added oldest first.
+    pub fn add_event(&mut self, event: &CounterEvent) {
+        self.events += 1;
+        match event.kind {
+            EventKind::Increment => self.increments += 1,
+            EventKind::Decrement => self.decrements += 1,
+            EventKind::Set | EventKind::Undo | EventKind::Redo => self.edits += 1,
+            EventKind::Reset => self.resets += 1,
+        }
+        if event.delta > 0 {
+            self.added = self.added.saturating_add(event.delta);
+        } else {
+            self.removed = self.removed.saturating_add(event.delta.saturating_neg());
+        }
+        self.first_change.get_or_insert(event.at);
+        self.last_change = Some(event.at);
+    }
+
+    pub fn add_session(&mut self, session: &Session) {
+        self.sessions += 1;
+        self.session_total = self.session_total.saturating_add(session.value);
+        self.session_best = Some(
+            self.session_best
+                .map_or(session.value, |best| best.max(session.value)),
+        );
+        let sessions = i64::try_from(self.sessions).unwrap_or(i64::MAX);
+        self.session_average = Some(self.session_total / sessions);
+    }
+}
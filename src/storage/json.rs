@@ -0,0 +1,325 @@
+use std::{collections::HashMap, fs, io, ops::RangeInclusive, path::PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    migration::{self, SCHEMA_VERSION},
+    models::{self, Counter, CounterEvent, Session},
+    stats::CounterStats,
+};
+
+use super::{split_logs, unused_backup, with_suffix, Document, Page, StorageError, Store, Unsaved};
+
+/// The whole document as one JSON file, as the app saved it before the
+/// database. Older formats are migrated when read.
+///
+/// The file can't be read in parts, so every query reads all of it.
+pub struct JsonStore {
+    path: PathBuf,
+}
+
+/// The document as written, with the schema version in front.
+#[derive(Serialize)]
+struct Versioned<'a> {
+    version: u64,
+    #[serde(flatten)]
+    document: &'a Document,
+}
+
+impl JsonStore {
+    pub const fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// The saved document with every counter's whole history. Data saved by
+    /// an older build is upgraded, after copying the file aside so the
+    /// original survives a failed or unwanted migration.
+    pub fn read(&self) -> Result<Document, StorageError> {
+        let contents = match fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Document::default()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let value = serde_json::from_slice::<serde_json::Value>(&contents)
+            .map_err(StorageError::Corrupt)?;
+        let saved = migration::version_of(&value);
+        if saved < SCHEMA_VERSION {
+            fs::copy(
+                &self.path,
+                with_suffix(&self.path, format!(".v{saved}.bak")),
+            )?;
+        }
+
+        let value = migration::migrate(value).map_err(StorageError::Migration)?;
+        let document = serde_json::from_value::<Document>(value).map_err(StorageError::Corrupt)?;
+        Ok(Document {
+            counters: document
+                .counters
+                .into_iter()
+                .map(Counter::with_log_summary)
+                .collect(),
+            ..document
+        })
+    }
+
+    /// The saved counter with `id` and its whole history, if there is one.
+    fn counter(&self, id: Uuid) -> Result<Option<Counter>, StorageError> {
+        Ok(self
+            .read()?
+            .counters
+            .into_iter()
+            .find(|counter| counter.id == id))
+    }
+}
+
+/// The entries of `log`, oldest first, that `page` covers counting from the
+/// newest.
+fn page_of<T>(log: Vec<T>, page: Page) -> Vec<T> {
+    log.into_iter()
+        .rev()
+        .skip(page.offset)
+        .take(page.limit)
+        .collect()
+}
+
+impl Store for JsonStore {
+    fn load(&self) -> Result<Document, StorageError> {
+        Ok(split_logs(self.read()?).0)
+    }
+
+    /// Keeps the history already in the file, converted to each counter's
+    /// current kind, and appends `unsaved` to it. Writes to a temporary file
+    /// first and renames it over the previous one, so an interrupted save
+    /// never leaves a half-written file.
+    fn save(&self, document: &Document, unsaved: &[Unsaved]) -> Result<(), StorageError> {
+        let mut saved = self
+            .read()?
+            .counters
+            .into_iter()
+            .map(|counter| (counter.id, counter))
+            .collect::<HashMap<_, _>>();
+        let counters = document
+            .counters
+            .iter()
+            .map(|counter| {
+                let (mut events, mut sessions) = match saved.remove(&counter.id) {
+                    Some(mut saved) => {
+                        models::rescale_log(
+                            &mut saved.events,
+                            &mut saved.sessions,
+                            saved.kind,
+                            counter.kind,
+                        );
+                        (saved.events, saved.sessions)
+                    }
+                    None => (Vec::new(), Vec::new()),
+                };
+                for batch in unsaved.iter().filter(|batch| batch.counter == counter.id) {
+                    let (mut new_events, mut new_sessions) =
+                        (batch.events.clone(), batch.sessions.clone());
+                    models::rescale_log(
+                        &mut new_events,
+                        &mut new_sessions,
+                        batch.kind,
+                        counter.kind,
+                    );
+                    events.extend(new_events);
+                    sessions.extend(new_sessions);
+                }
+
+                Counter {
+                    events,
+                    sessions,
+                    ..counter.clone()
+                }
+            })
+            .collect();
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let versioned = Versioned {
+            version: SCHEMA_VERSION,
+            document: &Document {
+                counters,
+                ..document.clone()
+            },
+        };
+        let contents = serde_json::to_vec_pretty(&versioned).map_err(StorageError::Serialize)?;
+        let temp = self.path.with_extension("json.tmp");
+        fs::write(&temp, contents)?;
+        fs::rename(&temp, &self.path)?;
+
+        Ok(())
+    }
+
+    fn events(
+        &self,
+        counter: Uuid,
+        span: RangeInclusive<DateTime<Utc>>,
+        page: Page,
+    ) -> Result<Vec<CounterEvent>, StorageError> {
+        let events = self
+            .counter(counter)?
+            .map(|counter| counter.events)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|event| span.contains(&event.at))
+            .collect();
+        Ok(page_of(events, page))
+    }
+
+    fn sessions(&self, counter: Uuid, page: Page) -> Result<Vec<Session>, StorageError> {
+        let sessions = self
+            .counter(counter)?
+            .map(|counter| counter.sessions)
+            .unwrap_or_default();
+        Ok(page_of(sessions, page))
+    }
+
+    fn stats(&self, counter: Uuid) -> Result<CounterStats, StorageError> {
+        let mut stats = CounterStats::default();
+        if let Some(counter) = self.counter(counter)? {
+            counter
+                .events
+                .iter()
+                .for_each(|event| stats.add_event(event));
+            counter
+                .sessions
+                .iter()
+                .for_each(|session| stats.add_session(session));
+        }
+        Ok(stats)
+    }
+
+    fn backup_corrupt(&self) -> Result<PathBuf, StorageError> {
+        let backup = unused_backup(&self.path, ".corrupt", &[""]);
+        fs::rename(&self.path, &backup)?;
+
+        Ok(backup)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{models::EventKind, storage::all_time, value::ValueKind};
+
+    const V0: &str = include_str!("../../fixtures/storage/v0.json");
+    const V2: &str = include_str!("../../fixtures/storage/v2.json");
+
+    /// A store in a directory of its own under the system's temporary directory.
+    fn store(contents: &str) -> (JsonStore, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("tallyfy-json-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("counters.json");
+        fs::write(&path, contents).unwrap();
+        (JsonStore::new(path), dir)
+    }
+
+    #[test]
+    fn writes_the_current_version_as_it_reads_it() {
+        let value = serde_json::from_str::<Value>(V2).unwrap();
+        let document = serde_json::from_value::<Document>(value.clone()).unwrap();
+        let versioned = Versioned {
+            version: SCHEMA_VERSION,
+            document: &document,
+        };
+
+        assert_eq!(serde_json::to_value(&versioned).unwrap(), value);
+    }
+
+    #[test]
+    fn keeps_a_copy_of_an_older_file_and_saves_it_upgraded() {
+        let (store, dir) = store(V0);
+
+        let document = store.load().unwrap();
+        assert_eq!(document.counters.len(), 2);
+        assert_eq!(
+            fs::read_to_string(dir.join("counters.json.v0.bak")).unwrap(),
+            V0
+        );
+
+        store.save(&document, &[]).unwrap();
+        let saved =
+            serde_json::from_slice::<Value>(&fs::read(dir.join("counters.json")).unwrap()).unwrap();
+        assert_eq!(migration::version_of(&saved), SCHEMA_VERSION);
+        assert_eq!(store.load().unwrap(), document);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_the_history_in_the_file_and_appends_to_it() {
+        let (store, dir) = store(V2);
+        let boxes = store.read().unwrap().counters[0].clone();
+        let document = store.load().unwrap();
+        assert!(document.counters[0].events.is_empty());
+
+        // Logged before the kind changed, so still in whole numbers.
+        let unsaved = Unsaved {
+            counter: boxes.id,
+            kind: boxes.kind,
+            events: vec![CounterEvent {
+                at: Utc::now(),
+                kind: EventKind::Increment,
+                delta: 2,
+                value: 4,
+            }],
+            sessions: Vec::new(),
+        };
+        let mut changed = document;
+        changed.counters[0].kind = ValueKind::Decimal { places: 1 };
+        store.save(&changed, &[unsaved]).unwrap();
+
+        assert_eq!(store.load().unwrap(), changed);
+        let values = store
+            .events(boxes.id, all_time(), Page::ALL)
+            .unwrap()
+            .into_iter()
+            .map(|event| event.value)
+            .collect::<Vec<_>>();
+        assert_eq!(values, [40, 20, 0, 40]);
+        assert_eq!(
+            store
+                .events(
+                    boxes.id,
+                    all_time(),
+                    Page {
+                        offset: 1,
+                        limit: 2
+                    }
+                )
+                .unwrap(),
+            store.read().unwrap().counters[0].events[1..3]
+                .iter()
+                .rev()
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(store.stats(boxes.id).unwrap().sessions, 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn loads_nothing_when_nothing_was_saved() {
+        let (store, dir) = store("");
+        fs::remove_file(dir.join("counters.json")).unwrap();
+
+        assert_eq!(store.load().unwrap(), Document::default());
+        assert!(!store.exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,244 @@
+use std::{
+    ffi::OsStr,
+    fmt, io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    migration::MigrationError,
+    models::{Counter, CounterEvent, FilterPreset, Group, Session},
+    sort::SortMode,
+    stats::CounterStats,
+    value::ValueKind,
+};
+
+pub mod json;
+pub mod sqlite;
+
+const JSON_FILE_NAME: &str = "counters.json";
+const DATABASE_FILE_NAME: &str = "counters.sqlite3";
+
+#[derive(Debug)]
+pub enum StorageError {
+    NoDataDir,
+    Io(io::Error),
+    Corrupt(serde_json::Error),
+    Migration(MigrationError),
+    Serialize(serde_json::Error),
+    Database(rusqlite::Error),
+    /// The database was created by a newer build with a different layout.
+    NewerDatabase(i64),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoDataDir => write!(f, "no data directory is available on this platform"),
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Corrupt(err) => write!(f, "saved counters are corrupt: {err}"),
+            Self::Migration(err) => write!(f, "{err}"),
+            Self::Serialize(err) => write!(f, "counters could not be serialized: {err}"),
+            Self::Database(err) => write!(f, "database error: {err}"),
+            Self::NewerDatabase(version) => write!(
+                f,
+                "the database was created by a newer version of Tallyfy (layout {version})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::NoDataDir | Self::NewerDatabase(_) => None,
+            Self::Io(err) => Some(err),
+            Self::Corrupt(err) | Self::Serialize(err) => Some(err),
+            Self::Migration(err) => Some(err),
+            Self::Database(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<rusqlite::Error> for StorageError {
+    fn from(err: rusqlite::Error) -> Self {
+        Self::Database(err)
+    }
+}
+
+/// Everything saved to disk. Loaded counters carry no log entries; their
+/// history is read from the store as it is needed.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Document {
+    pub counters: Vec<Counter>,
+    #[serde(default)]
+    pub presets: Vec<FilterPreset>,
+    #[serde(default)]
+    pub sort: SortMode,
+    #[serde(default)]
+    pub groups: Vec<Group>,
+}
+
+/// Log entries of one counter that the store doesn't have yet, in the kind
+/// the counter had when they were logged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsaved {
+    pub counter: Uuid,
+    pub kind: ValueKind,
+    pub events: Vec<CounterEvent>,
+    pub sessions: Vec<Session>,
+}
+
+/// Which entries of a log to read, counted from the newest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Page {
+    pub const ALL: Self = Self::first(usize::MAX);
+
+    pub const fn first(limit: usize) -> Self {
+        Self { offset: 0, limit }
+    }
+}
+
+/// A span covering every entry a log can hold.
+pub fn all_time() -> RangeInclusive<DateTime<Utc>> {
+    // The end is the last moment of year 9999, after which dates would no
+    // longer sort as text in the database.
+    let end = DateTime::from_timestamp(253_402_300_799, 999_999_999).unwrap_or_default();
+    DateTime::UNIX_EPOCH..=end
+}
+
+/// `counter` with its whole history read back from `store`, oldest first,
+/// followed by any entries it has that the store doesn't.
+pub fn with_history(store: &dyn Store, counter: Counter) -> Result<Counter, StorageError> {
+    let mut events = store.events(counter.id, all_time(), Page::ALL)?;
+    let mut sessions = store.sessions(counter.id, Page::ALL)?;
+    events.reverse();
+    sessions.reverse();
+    events.extend(counter.events);
+    sessions.extend(counter.sessions);
+
+    Ok(Counter {
+        events,
+        sessions,
+        ..counter
+    })
+}
+
+/// Where the document and every counter's history are kept between runs.
+pub trait Store {
+    /// Reads the saved data. Nothing saved yet is not an error and yields an
+    /// empty document.
+    fn load(&self) -> Result<Document, StorageError>;
+
+    /// Replaces the saved data and appends `unsaved` to the logs, converting
+    /// the history of any counter whose kind changed. Counters missing from
+    /// `document` are removed along with their history. A save that fails or
+    /// is interrupted leaves the previous data intact.
+    fn save(&self, document: &Document, unsaved: &[Unsaved]) -> Result<(), StorageError>;
+
+    /// The events of `counter` logged within `span`, newest first.
+    fn events(
+        &self,
+        counter: Uuid,
+        span: RangeInclusive<DateTime<Utc>>,
+        page: Page,
+    ) -> Result<Vec<CounterEvent>, StorageError>;
+
+    /// The sessions of `counter`, newest first.
+    fn sessions(&self, counter: Uuid, page: Page) -> Result<Vec<Session>, StorageError>;
+
+    /// Figures summing up the whole history of `counter`.
+    fn stats(&self, counter: Uuid) -> Result<CounterStats, StorageError>;
+
+    /// Moves unreadable data aside so a fresh store can be written without
+    /// destroying the original. Returns where it went.
+    fn backup_corrupt(&self) -> Result<PathBuf, StorageError>;
+}
+
+/// The platform data directory the app saves into.
+pub fn data_dir() -> Result<PathBuf, StorageError> {
+    ProjectDirs::from("", "", "tallyfy")
+        .map(|dirs| dirs.data_dir().to_path_buf())
+        .ok_or(StorageError::NoDataDir)
+}
+
+/// The store the app uses: an embedded database in the data directory. Data
+/// from builds that saved a JSON file is copied into it the first time it is
+/// loaded.
+pub fn open() -> Rc<dyn Store> {
+    let dir = data_dir().ok();
+    Rc::new(sqlite::SqliteStore::new(
+        dir.as_ref().map(|dir| dir.join(DATABASE_FILE_NAME)),
+        dir.map(|dir| json::JsonStore::new(dir.join(JSON_FILE_NAME))),
+    ))
+}
+
+/// `document` without the logs of its counters, as [`Store::load`] returns
+/// it, and the logs as entries for [`Store::save`].
+fn split_logs(document: Document) -> (Document, Vec<Unsaved>) {
+    let mut unsaved = Vec::new();
+    let counters = document
+        .counters
+        .into_iter()
+        .map(|counter| {
+            let (counter, events, sessions) = counter.take_logs();
+            unsaved.push(Unsaved {
+                counter: counter.id,
+                kind: counter.kind,
+                events,
+                sessions,
+            });
+            counter
+        })
+        .collect();
+
+    (
+        Document {
+            counters,
+            ..document
+        },
+        unsaved,
+    )
+}
+
+/// `path` with `suffix` appended to its file name, for backups.
+fn with_suffix(path: &Path, suffix: impl AsRef<OsStr>) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(suffix);
+    PathBuf::from(backup)
+}
+
+/// Where to move `path` aside without replacing an earlier backup: `path`
+/// with `suffix`, or with a number after it if that name is taken. The name
+/// counts as taken if any of `companions` appended to it exists.
+fn unused_backup(path: &Path, suffix: &str, companions: &[&str]) -> PathBuf {
+    let taken = |backup: &PathBuf| {
+        companions
+            .iter()
+            .any(|companion| with_suffix(backup, companion).exists())
+    };
+    let mut backup = with_suffix(path, suffix);
+    let mut number = 1_u64;
+    while taken(&backup) {
+        number += 1;
+        backup = with_suffix(path, format!("{suffix}.{number}"));
+    }
+    backup
+}
@@ -0,0 +1,941 @@
+use std::{
+    cell::{RefCell, RefMut},
+    collections::HashMap,
+    fs,
+    ops::RangeInclusive,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row, Transaction};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    models::{Counter, CounterEvent, Limits, Session},
+    stats::CounterStats,
+    value::ValueKind,
+};
+
+use super::{
+    json::JsonStore, split_logs, unused_backup, with_suffix, Document, Page, StorageError, Store,
+    Unsaved,
+};
+
+/// Version of the tables below, kept in `PRAGMA user_version`.
+const DATABASE_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE counters (
+    id BLOB PRIMARY KEY,
+    position INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    count INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    color TEXT NOT NULL,
+    custom_color TEXT,
+    step INTEGER NOT NULL,
+    min INTEGER,
+    max INTEGER,
+    goal INTEGER,
+    reset_value INTEGER NOT NULL,
+    schedule TEXT,
+    updated_at TEXT,
+    group_id BLOB,
+    tags TEXT NOT NULL,
+    notes TEXT NOT NULL,
+    icon TEXT,
+    first_change TEXT,
+    last_reset TEXT
+);
+
+-- History is read one counter at a time, a page at a time from the newest,
+-- so the logs are indexed by counter and time. The id keeps entries logged
+-- at the same moment in order.
+CREATE TABLE events (
+    id INTEGER PRIMARY KEY,
+    counter_id BLOB NOT NULL REFERENCES counters (id) ON DELETE CASCADE,
+    at TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    delta INTEGER NOT NULL,
+    value INTEGER NOT NULL
+);
+
+CREATE INDEX events_by_time ON events (counter_id, at);
+
+CREATE TABLE sessions (
+    id INTEGER PRIMARY KEY,
+    counter_id BLOB NOT NULL REFERENCES counters (id) ON DELETE CASCADE,
+    started_at TEXT,
+    ended_at TEXT NOT NULL,
+    value INTEGER NOT NULL
+);
+
+CREATE INDEX sessions_by_time ON sessions (counter_id, ended_at);
+
+CREATE TABLE settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+/// Counters, their history and the app settings in an embedded database.
+///
+/// Every save runs in one transaction, so a crash mid-save leaves the
+/// previous state. Logs only grow, so a save appends the entries it is
+/// handed and never rewrites the rest, and history is read back a page at a
+/// time instead of along with the counters.
+pub struct SqliteStore {
+    path: Option<PathBuf>,
+    /// Handed over to the database on the first load, if nothing has been
+    /// saved there yet.
+    legacy: Option<JsonStore>,
+    connection: RefCell<Option<Connection>>,
+}
+
+/// A counter row before its structured fields are decoded.
+struct CounterRow {
+    counter: Counter,
+    kind: String,
+    color: String,
+    custom_color: Option<String>,
+    schedule: Option<String>,
+    tags: String,
+    icon: Option<String>,
+}
+
+impl SqliteStore {
+    /// The connection is opened on first use, so errors surface from `load`
+    /// and `save`. Without a `path` every call fails with `NoDataDir`.
+    pub const fn new(path: Option<PathBuf>, legacy: Option<JsonStore>) -> Self {
+        Self {
+            path,
+            legacy,
+            connection: RefCell::new(None),
+        }
+    }
+
+    fn connection(&self) -> Result<RefMut<'_, Connection>, StorageError> {
+        let mut slot = self.connection.borrow_mut();
+        if slot.is_none() {
+            let path = self.path.as_ref().ok_or(StorageError::NoDataDir)?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let connection = Connection::open(path)?;
+            connection.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+            connection.pragma_update(None, "foreign_keys", true)?;
+            create_tables(&connection)?;
+            *slot = Some(connection);
+        }
+
+        Ok(RefMut::map(slot, |slot| {
+            slot.as_mut().expect("the connection was opened above")
+        }))
+    }
+
+    /// Whether the app has saved to the database, rather than only to the
+    /// JSON file of earlier builds.
+    fn has_saved(&self) -> Result<bool, StorageError> {
+        let connection = self.connection()?;
+        let saved = connection.query_row("SELECT EXISTS (SELECT 1 FROM settings)", [], |row| {
+            row.get(0)
+        })?;
+        Ok(saved)
+    }
+
+    fn legacy(&self) -> Option<&JsonStore> {
+        self.legacy.as_ref().filter(|legacy| legacy.exists())
+    }
+}
+
+impl Store for SqliteStore {
+    /// Data an earlier build saved as JSON is copied into the database, history
+    /// included, the first time it is loaded. The file is left where it was.
+    fn load(&self) -> Result<Document, StorageError> {
+        if !self.has_saved()? {
+            if let Some(legacy) = self.legacy() {
+                let (document, unsaved) = split_logs(legacy.read()?);
+                self.save(&document, &unsaved)?;
+                return Ok(document);
+            }
+        }
+
+        let connection = self.connection()?;
+        let counters = load_counters(&connection)?;
+
+        let setting = |key: &str| {
+            connection
+                .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+                    row.get::<_, String>(0)
+                })
+                .optional()
+        };
+        let presets = setting("presets")?;
+        let sort = setting("sort")?;
+        let groups = setting("groups")?;
+
+        Ok(Document {
+            counters,
+            presets: presets
+                .as_deref()
+                .map(decode)
+                .transpose()?
+                .unwrap_or_default(),
+            sort: sort.as_deref().map(decode).transpose()?.unwrap_or_default(),
+            groups: groups
+                .as_deref()
+                .map(decode)
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+
+    fn save(&self, document: &Document, unsaved: &[Unsaved]) -> Result<(), StorageError> {
+        let mut connection = self.connection()?;
+        let transaction = connection.transaction()?;
+
+        let kinds = document
+            .counters
+            .iter()
+            .map(|counter| (counter.id, counter.kind))
+            .collect::<HashMap<_, _>>();
+        let stored = transaction
+            .prepare("SELECT id, kind FROM counters")?
+            .query_map([], |row| {
+                Ok((row.get::<_, Uuid>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (id, stored_kind) in stored {
+            match kinds.get(&id) {
+                None => {
+                    transaction.execute("DELETE FROM counters WHERE id = ?1", [id])?;
+                }
+                Some(&kind) => {
+                    let stored_kind = decode::<ValueKind>(&stored_kind)?;
+                    if stored_kind != kind {
+                        rescale_history(&transaction, id, stored_kind, kind)?;
+                    }
+                }
+            }
+        }
+
+        for (position, counter) in document.counters.iter().enumerate() {
+            save_counter(&transaction, position, counter)?;
+        }
+
+        // Entries of counters deleted since they were logged went with them.
+        for batch in unsaved {
+            if let Some(&kind) = kinds.get(&batch.counter) {
+                append(&transaction, batch, kind)?;
+            }
+        }
+
+        for (key, value) in [
+            ("presets", encode(&document.presets)?),
+            ("sort", encode(&document.sort)?),
+            ("groups", encode(&document.groups)?),
+        ] {
+            transaction.execute(
+                "INSERT INTO settings (key, value) VALUES (?1, ?2)
+                 ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+                params![key, value],
+            )?;
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn events(
+        &self,
+        counter: Uuid,
+        span: RangeInclusive<DateTime<Utc>>,
+        page: Page,
+    ) -> Result<Vec<CounterEvent>, StorageError> {
+        let connection = self.connection()?;
+        let mut statement = connection.prepare_cached(
+            "SELECT at, kind, delta, value FROM events
+             WHERE counter_id = ?1 AND at BETWEEN ?2 AND ?3
+             ORDER BY at DESC, id DESC LIMIT ?4 OFFSET ?5",
+        )?;
+        let mut rows = statement.query(params![
+            counter,
+            span.start(),
+            span.end(),
+            sql_count(page.limit),
+            sql_count(page.offset),
+        ])?;
+
+        let mut events = Vec::new();
+        while let Some(row) = rows.next()? {
+            events.push(read_event(row)?);
+        }
+        Ok(events)
+    }
+
+    fn sessions(&self, counter: Uuid, page: Page) -> Result<Vec<Session>, StorageError> {
+        let connection = self.connection()?;
+        let sessions = connection
+            .prepare_cached(
+                "SELECT started_at, ended_at, value FROM sessions WHERE counter_id = ?1
+                 ORDER BY ended_at DESC, id DESC LIMIT ?2 OFFSET ?3",
+            )?
+            .query_map(
+                params![counter, sql_count(page.limit), sql_count(page.offset)],
+                read_session,
+            )?
+            .collect::<Result<_, _>>()?;
+        Ok(sessions)
+    }
+
+    /// Reads the history a row at a time, so it is never held in memory.
+    fn stats(&self, counter: Uuid) -> Result<CounterStats, StorageError> {
+        let connection = self.connection()?;
+        let mut stats = CounterStats::default();
+
+        let mut events = connection.prepare_cached(
+            "SELECT at, kind, delta, value FROM events WHERE counter_id = ?1 ORDER BY at, id",
+        )?;
+        let mut rows = events.query([counter])?;
+        while let Some(row) = rows.next()? {
+            stats.add_event(&read_event(row)?);
+        }
+
+        let mut sessions = connection.prepare_cached(
+            "SELECT started_at, ended_at, value FROM sessions WHERE counter_id = ?1
+             ORDER BY ended_at, id",
+        )?;
+        let mut rows = sessions.query([counter])?;
+        while let Some(row) = rows.next()? {
+            stats.add_session(&read_session(row)?);
+        }
+
+        Ok(stats)
+    }
+
+    /// Moves the database aside, or the JSON file of an earlier build if that
+    /// is what failed to load. A database from a newer build is not corrupt,
+    /// so it is left for that build to open.
+    fn backup_corrupt(&self) -> Result<PathBuf, StorageError> {
+        if let Err(err @ StorageError::NewerDatabase(_)) = self.connection() {
+            return Err(err);
+        }
+        if let (Ok(false), Some(legacy)) = (self.has_saved(), self.legacy()) {
+            return legacy.backup_corrupt();
+        }
+
+        self.connection.replace(None);
+        let path = self.path.as_ref().ok_or(StorageError::NoDataDir)?;
+        let backup = unused_backup(path, ".corrupt", &["", "-wal", "-shm"]);
+        fs::rename(path, &backup)?;
+        for suffix in ["-wal", "-shm"] {
+            let companion = with_suffix(path, suffix);
+            if companion.exists() {
+                fs::rename(&companion, with_suffix(&backup, suffix))?;
+            }
+        }
+
+        Ok(backup)
+    }
+}
+
+/// The counters without their history, which is read as it is needed.
+fn load_counters(connection: &Connection) -> Result<Vec<Counter>, StorageError> {
+    let rows = connection
+        .prepare(
+            "SELECT id, title, count, kind, color, custom_color, step, min, max, goal,
+                    reset_value, schedule, updated_at, group_id, tags, notes, icon,
+                    first_change, last_reset
+             FROM counters ORDER BY position",
+        )?
+        .query_map([], |row| {
+            Ok(CounterRow {
+                counter: Counter {
+                    id: row.get("id")?,
+                    title: row.get("title")?,
+                    count: row.get("count")?,
+                    step: row.get("step")?,
+                    limits: Limits {
+                        min: row.get("min")?,
+                        max: row.get("max")?,
+                        goal: row.get("goal")?,
+                    },
+                    reset_value: row.get("reset_value")?,
+                    updated_at: row.get("updated_at")?,
+                    group: row.get("group_id")?,
+                    notes: row.get("notes")?,
+                    first_change: row.get("first_change")?,
+                    last_reset: row.get("last_reset")?,
+                    ..Counter::default()
+                },
+                kind: row.get("kind")?,
+                color: row.get("color")?,
+                custom_color: row.get("custom_color")?,
+                schedule: row.get("schedule")?,
+                tags: row.get("tags")?,
+                icon: row.get("icon")?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(Counter {
+                kind: decode(&row.kind)?,
+                color: decode(&row.color)?,
+                custom_color: row.custom_color.as_deref().map(decode).transpose()?,
+                schedule: row.schedule.as_deref().map(decode).transpose()?,
+                tags: decode(&row.tags)?,
+                icon: row.icon.as_deref().map(decode).transpose()?,
+                ..row.counter
+            })
+        })
+        .collect()
+}
+
+fn create_tables(connection: &Connection) -> Result<(), StorageError> {
+    let version =
+        connection.pragma_query_value(None, "user_version", |row| row.get::<_, i64>(0))?;
+    match version {
+        0 => {
+            connection.execute_batch(SCHEMA)?;
+            connection.pragma_update(None, "user_version", DATABASE_VERSION)?;
+            Ok(())
+        }
+        DATABASE_VERSION => Ok(()),
+        newer => Err(StorageError::NewerDatabase(newer)),
+    }
+}
+
+fn save_counter(
+    transaction: &Transaction<'_>,
+    position: usize,
+    counter: &Counter,
+) -> Result<(), StorageError> {
+    transaction
+        .prepare_cached(
+            "INSERT INTO counters (id, position, title, count, kind, color, custom_color, step,
+                                   min, max, goal, reset_value, schedule, updated_at, group_id,
+                                   tags, notes, icon, first_change, last_reset)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+                     ?18, ?19, ?20)
+             ON CONFLICT (id) DO UPDATE SET
+                 position = excluded.position, title = excluded.title, count = excluded.count,
+                 kind = excluded.kind, color = excluded.color,
+                 custom_color = excluded.custom_color, step = excluded.step, min = excluded.min,
+                 max = excluded.max, goal = excluded.goal, reset_value = excluded.reset_value,
+                 schedule = excluded.schedule, updated_at = excluded.updated_at,
+                 group_id = excluded.group_id, tags = excluded.tags, notes = excluded.notes,
+                 icon = excluded.icon, first_change = excluded.first_change,
+                 last_reset = excluded.last_reset",
+        )?
+        .execute(params![
+            counter.id,
+            i64::try_from(position).unwrap_or(i64::MAX),
+            counter.title,
+            counter.count,
+            encode(&counter.kind)?,
+            encode(&counter.color)?,
+            counter.custom_color.as_ref().map(encode).transpose()?,
+            counter.step,
+            counter.limits.min,
+            counter.limits.max,
+            counter.limits.goal,
+            counter.reset_value,
+            counter.schedule.as_ref().map(encode).transpose()?,
+            counter.updated_at,
+            counter.group,
+            encode(&counter.tags)?,
+            counter.notes,
+            counter.icon.as_ref().map(encode).transpose()?,
+            counter.first_change,
+            counter.last_reset,
+        ])?;
+
+    Ok(())
+}
+
+/// Appends the entries of `batch` to the logs, converted to `kind`.
+fn append(
+    transaction: &Transaction<'_>,
+    batch: &Unsaved,
+    kind: ValueKind,
+) -> Result<(), StorageError> {
+    let rescale = |raw: i64| batch.kind.rescale(raw, kind);
+
+    let mut insert = transaction.prepare_cached(
+        "INSERT INTO events (counter_id, at, kind, delta, value) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for event in &batch.events {
+        insert.execute(params![
+            batch.counter,
+            event.at,
+            encode(&event.kind)?,
+            rescale(event.delta),
+            rescale(event.value),
+        ])?;
+    }
+
+    let mut insert = transaction.prepare_cached(
+        "INSERT INTO sessions (counter_id, started_at, ended_at, value) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for session in &batch.sessions {
+        insert.execute(params![
+            batch.counter,
+            session.started_at,
+            session.ended_at,
+            rescale(session.value),
+        ])?;
+    }
+
+    Ok(())
+}
+
+/// Converts the stored history of `counter` from `from` into `to`, after its
+/// kind was changed.
+fn rescale_history(
+    transaction: &Transaction<'_>,
+    counter: Uuid,
+    from: ValueKind,
+    to: ValueKind,
+) -> Result<(), StorageError> {
+    let rescale = |raw: i64| from.rescale(raw, to);
+
+    let events = transaction
+        .prepare_cached("SELECT id, delta, value FROM events WHERE counter_id = ?1")?
+        .query_map([counter], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut update =
+        transaction.prepare_cached("UPDATE events SET delta = ?2, value = ?3 WHERE id = ?1")?;
+    for (id, delta, value) in events {
+        update.execute(params![id, rescale(delta), rescale(value)])?;
+    }
+
+    let sessions = transaction
+        .prepare_cached("SELECT id, value FROM sessions WHERE counter_id = ?1")?
+        .query_map([counter], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut update = transaction.prepare_cached("UPDATE sessions SET value = ?2 WHERE id = ?1")?;
+    for (id, value) in sessions {
+        update.execute(params![id, rescale(value)])?;
+    }
+
+    Ok(())
+}
+
+/// An event from a row of `at, kind, delta, value`.
+fn read_event(row: &Row<'_>) -> Result<CounterEvent, StorageError> {
+    Ok(CounterEvent {
+        at: row.get(0)?,
+        kind: decode(&row.get::<_, String>(1)?)?,
+        delta: row.get(2)?,
+        value: row.get(3)?,
+    })
+}
+
+/// A session from a row of `started_at, ended_at, value`.
+fn read_session(row: &Row<'_>) -> rusqlite::Result<Session> {
+    Ok(Session {
+        started_at: row.get(0)?,
+        ended_at: row.get(1)?,
+        value: row.get(2)?,
+    })
+}
+
+/// `count` as a `LIMIT` or `OFFSET`, where `usize::MAX` stands for no limit.
+fn sql_count(count: usize) -> i64 {
+    i64::try_from(count).unwrap_or(i64::MAX)
+}
+
+/// Structured fields are stored as JSON, with plain strings unquoted so
+/// names like `Increment` stay readable.
+fn encode<T: Serialize>(value: &T) -> Result<String, StorageError> {
+    match serde_json::to_value(value).map_err(StorageError::Serialize)? {
+        Value::String(text) => Ok(text),
+        other => Ok(other.to_string()),
+    }
+}
+
+fn decode<T: DeserializeOwned>(text: &str) -> Result<T, StorageError> {
+    serde_json::from_str(text)
+        .or_else(|_| serde_json::from_value(Value::String(text.to_string())))
+        .map_err(StorageError::Corrupt)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{models::EventKind, storage::all_time};
+
+    const V2: &str = include_str!("../../fixtures/storage/v2.json");
+
+    /// A directory of its own under the system's temporary directory.
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tallyfy-sqlite-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A store in `dir` that hands over `counters.json` beside it.
+    fn store(dir: &Path) -> SqliteStore {
+        SqliteStore::new(
+            Some(dir.join("counters.sqlite3")),
+            Some(JsonStore::new(dir.join("counters.json"))),
+        )
+    }
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 1, 12, minute, 0).unwrap()
+    }
+
+    fn set(minute: u32, value: i64) -> CounterEvent {
+        CounterEvent {
+            at: at(minute),
+            kind: EventKind::Set,
+            delta: 1,
+            value,
+        }
+    }
+
+    fn session(minute: u32, value: i64) -> Session {
+        Session {
+            started_at: None,
+            ended_at: at(minute),
+            value,
+        }
+    }
+
+    fn counter(title: &str) -> Counter {
+        Counter {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            ..Counter::default()
+        }
+    }
+
+    fn logged(counter: &Counter, events: Vec<CounterEvent>, sessions: Vec<Session>) -> Unsaved {
+        Unsaved {
+            counter: counter.id,
+            kind: counter.kind,
+            events,
+            sessions,
+        }
+    }
+
+    fn document(counters: Vec<Counter>) -> Document {
+        Document {
+            counters,
+            ..Document::default()
+        }
+    }
+
+    fn rows(store: &SqliteStore, table: &str) -> i64 {
+        store
+            .connection()
+            .unwrap()
+            .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn hands_data_saved_as_json_over_to_the_database() {
+        let dir = temp_dir();
+        fs::write(dir.join("counters.json"), V2).unwrap();
+        let legacy = JsonStore::new(dir.join("counters.json")).read().unwrap();
+
+        let store = store(&dir);
+        assert!(!store.has_saved().unwrap());
+        let document = store.load().unwrap();
+        assert!(store.has_saved().unwrap());
+        assert_eq!(document, split_logs(legacy.clone()).0);
+        for counter in &legacy.counters {
+            let mut events = store.events(counter.id, all_time(), Page::ALL).unwrap();
+            let mut sessions = store.sessions(counter.id, Page::ALL).unwrap();
+            events.reverse();
+            sessions.reverse();
+            assert_eq!(events, counter.events);
+            assert_eq!(sessions, counter.sessions);
+        }
+
+        // From now on the database is read, even once the file is gone.
+        drop(store);
+        fs::remove_file(dir.join("counters.json")).unwrap();
+        assert_eq!(self::store(&dir).load().unwrap(), document);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn appends_new_entries_and_keeps_the_rest() {
+        let dir = temp_dir();
+        let store = store(&dir);
+        let coffee = Counter {
+            count: 3,
+            first_change: Some(at(0)),
+            last_reset: Some(at(3)),
+            ..counter("Coffee")
+        };
+        let saved = document(vec![coffee.clone()]);
+
+        store
+            .save(
+                &saved,
+                &[logged(&coffee, vec![set(0, 1), set(1, 2)], vec![])],
+            )
+            .unwrap();
+        store
+            .save(
+                &saved,
+                &[logged(&coffee, vec![set(2, 3)], vec![session(3, 3)])],
+            )
+            .unwrap();
+        store.save(&saved, &[]).unwrap();
+
+        assert_eq!(store.load().unwrap(), saved);
+        assert_eq!(
+            store.events(coffee.id, all_time(), Page::ALL).unwrap(),
+            [set(2, 3), set(1, 2), set(0, 1)]
+        );
+        assert_eq!(
+            store.sessions(coffee.id, Page::ALL).unwrap(),
+            [session(3, 3)]
+        );
+
+        let stats = store.stats(coffee.id).unwrap();
+        assert_eq!(stats.events, 3);
+        assert_eq!(stats.edits, 3);
+        assert_eq!(stats.first_change, Some(at(0)));
+        assert_eq!(stats.last_change, Some(at(2)));
+        assert_eq!(stats.sessions, 1);
+        assert_eq!(stats.session_best, Some(3));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn converts_the_history_when_the_kind_changes() {
+        let dir = temp_dir();
+        let store = store(&dir);
+        let weight = counter("Weight");
+        store
+            .save(
+                &document(vec![weight.clone()]),
+                &[logged(&weight, vec![set(0, 2)], vec![session(1, 2)])],
+            )
+            .unwrap();
+
+        // Logged before the change, so still in the old kind.
+        let unsaved = logged(&weight, vec![set(2, 3)], vec![]);
+        let weight = Counter {
+            kind: ValueKind::Decimal { places: 2 },
+            ..weight
+        };
+        store
+            .save(&document(vec![weight.clone()]), &[unsaved])
+            .unwrap();
+
+        let events = store.events(weight.id, all_time(), Page::ALL).unwrap();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (event.delta, event.value))
+                .collect::<Vec<_>>(),
+            [(100, 300), (100, 200)]
+        );
+        assert_eq!(
+            store.sessions(weight.id, Page::ALL).unwrap(),
+            [session(1, 200)]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn deleting_a_counter_deletes_its_history() {
+        let dir = temp_dir();
+        let store = store(&dir);
+        let kept = counter("Kept");
+        let deleted = counter("Deleted");
+        store
+            .save(
+                &document(vec![kept.clone(), deleted.clone()]),
+                &[
+                    logged(&kept, vec![set(0, 1)], vec![session(1, 1)]),
+                    logged(&deleted, vec![set(0, 1), set(1, 2)], vec![session(2, 2)]),
+                ],
+            )
+            .unwrap();
+
+        // Entries logged just before the deletion go with it.
+        store
+            .save(
+                &document(vec![kept.clone()]),
+                &[logged(&deleted, vec![set(3, 3)], vec![])],
+            )
+            .unwrap();
+
+        assert_eq!(store.load().unwrap(), document(vec![kept]));
+        assert!(store
+            .events(deleted.id, all_time(), Page::ALL)
+            .unwrap()
+            .is_empty());
+        assert!(store.sessions(deleted.id, Page::ALL).unwrap().is_empty());
+        assert_eq!(rows(&store, "events"), 1);
+        assert_eq!(rows(&store, "sessions"), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reads_the_history_in_pages_and_spans() {
+        let dir = temp_dir();
+        let store = store(&dir);
+        let steps = counter("Steps");
+        let mut events = (0..10)
+            .map(|minute| set(minute, i64::from(minute)))
+            .collect::<Vec<_>>();
+        // Logged in the same moment, so only the order they came in tells
+        // them apart.
+        events.extend([set(10, 100), set(10, 101)]);
+        store
+            .save(
+                &document(vec![steps.clone()]),
+                &[logged(&steps, events, vec![session(1, 1), session(2, 2)])],
+            )
+            .unwrap();
+
+        let values = |span, page| {
+            store
+                .events(steps.id, span, page)
+                .unwrap()
+                .into_iter()
+                .map(|event| event.value)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(all_time(), Page::first(3)), [101, 100, 9]);
+        assert_eq!(
+            values(
+                all_time(),
+                Page {
+                    offset: 3,
+                    limit: 2
+                }
+            ),
+            [8, 7]
+        );
+        assert_eq!(values(at(3)..=at(5), Page::ALL), [5, 4, 3]);
+        assert_eq!(values(at(3)..=at(5), Page::first(1)), [5]);
+        assert_eq!(
+            store.sessions(steps.id, Page::first(1)).unwrap(),
+            [session(2, 2)]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn moves_the_database_aside_with_its_journal() {
+        let dir = temp_dir();
+        let path = dir.join("counters.sqlite3");
+        let store = store(&dir);
+        store.save(&document(vec![counter("Coffee")]), &[]).unwrap();
+
+        // While another connection is open, closing ours leaves the journal
+        // files in place.
+        let other = Connection::open(&path).unwrap();
+        let _: i64 = other
+            .query_row("SELECT COUNT(*) FROM counters", [], |row| row.get(0))
+            .unwrap();
+        let backup = store.backup_corrupt().unwrap();
+
+        assert_eq!(backup, dir.join("counters.sqlite3.corrupt"));
+        for suffix in ["", "-wal", "-shm"] {
+            assert!(!with_suffix(&path, suffix).exists(), "{suffix}");
+            assert!(with_suffix(&backup, suffix).exists(), "{suffix}");
+        }
+
+        drop(other);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_earlier_backups() {
+        let dir = temp_dir();
+        let store = store(&dir);
+        store.save(&document(vec![counter("Coffee")]), &[]).unwrap();
+        let first = store.backup_corrupt().unwrap();
+        store.save(&document(vec![counter("Tea")]), &[]).unwrap();
+        let second = store.backup_corrupt().unwrap();
+
+        assert_eq!(first, dir.join("counters.sqlite3.corrupt"));
+        assert_eq!(second, dir.join("counters.sqlite3.corrupt.2"));
+        let titles = |backup: &Path| {
+            Connection::open(backup)
+                .unwrap()
+                .query_row("SELECT title FROM counters", [], |row| {
+                    row.get::<_, String>(0)
+                })
+                .unwrap()
+        };
+        assert_eq!(titles(&first), "Coffee");
+        assert_eq!(titles(&second), "Tea");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn leaves_a_newer_database_in_place() {
+        let dir = temp_dir();
+        let path = dir.join("counters.sqlite3");
+        Connection::open(&path)
+            .unwrap()
+            .pragma_update(None, "user_version", DATABASE_VERSION + 1)
+            .unwrap();
+
+        let store = store(&dir);
+        assert!(matches!(store.load(), Err(StorageError::NewerDatabase(_))));
+        assert!(matches!(
+            store.backup_corrupt(),
+            Err(StorageError::NewerDatabase(_))
+        ));
+        assert!(path.exists());
+        assert!(!dir.join("counters.sqlite3.corrupt").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn moves_a_corrupt_json_file_aside_while_nothing_was_saved() {
+        let dir = temp_dir();
+        fs::write(dir.join("counters.json"), "{").unwrap();
+
+        let store = store(&dir);
+        assert!(matches!(store.load(), Err(StorageError::Corrupt(_))));
+        let backup = store.backup_corrupt().unwrap();
+
+        assert_eq!(backup, dir.join("counters.json.corrupt"));
+        assert_eq!(fs::read_to_string(backup).unwrap(), "{");
+        assert!(!dir.join("counters.json").exists());
+        assert!(dir.join("counters.sqlite3").exists());
+        assert_eq!(store.load().unwrap(), Document::default());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,285 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+pub const MAX_DECIMAL_PLACES: u8 = 6;
+
+/// How a counter's raw `i64` value is interpreted.
+///
+/// Decimal counters store fixed-point values: with `places: 2` the raw value
+/// `1234` is displayed as `12.34`.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum ValueKind {
+    #[default]
+    Unsigned,
+    Signed,
+    Decimal {
+        places: u8,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseValueError {
+    Empty,
+    Invalid,
+    Negative,
+    NotWhole,
+    TooManyDecimals(u8),
+    OutOfRange,
+}
+
+impl fmt::Display for ParseValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "enter a value"),
+            Self::Invalid => write!(f, "not a number"),
+            Self::Negative => write!(f, "must not be negative"),
+            Self::NotWhole => write!(f, "must be a whole number"),
+            Self::TooManyDecimals(places) => write!(f, "at most {places} decimal places"),
+            Self::OutOfRange => write!(f, "number is too large"),
+        }
+    }
+}
+
+impl std::error::Error for ParseValueError {}
+
+impl ValueKind {
+    /// The most precise kind, used to compare values of counters with different kinds.
+    pub const FINEST: Self = Self::Decimal {
+        places: MAX_DECIMAL_PLACES,
+    };
+
+    pub const fn places(self) -> u8 {
+        match self {
+            Self::Unsigned | Self::Signed => 0,
+            Self::Decimal { places } => places,
+        }
+    }
+
+    /// Smallest raw value a counter of this kind may hold.
+    pub const fn floor(self) -> i64 {
+        match self {
+            Self::Unsigned => 0,
+            Self::Signed | Self::Decimal { .. } => i64::MIN,
+        }
+    }
+
+    /// Raw value representing one whole unit.
+    pub fn unit(self) -> i64 {
+        10_i64.pow(u32::from(self.places()))
+    }
+
+    pub fn format(self, raw: i64) -> String {
+        let places = usize::from(self.places());
+        if places == 0 {
+            return raw.to_string();
+        }
+
+        let unit = self.unit().unsigned_abs();
+        let sign = if raw < 0 { "-" } else { "" };
+        let magnitude = raw.unsigned_abs();
+        format!("{sign}{}.{:0places$}", magnitude / unit, magnitude % unit)
+    }
+
+    /// Formats a change in value, always showing its sign.
+    pub fn format_delta(self, raw: i64) -> String {
+        if raw > 0 {
+            format!("+{}", self.format(raw))
+        } else {
+            self.format(raw)
+        }
+    }
+
+    pub fn parse(self, text: &str) -> Result<i64, ParseValueError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(ParseValueError::Empty);
+        }
+
+        let negative = text.starts_with('-');
+        let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+        let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+            return Err(ParseValueError::Invalid);
+        }
+
+        let places = self.places();
+        if digits.contains('.') && places == 0 {
+            return Err(ParseValueError::NotWhole);
+        }
+        if fraction.len() > usize::from(places) {
+            return Err(ParseValueError::TooManyDecimals(places));
+        }
+
+        // Summed in a wider type so the magnitude of `i64::MIN`, one more than
+        // `i64::MAX`, can be read before its sign is applied.
+        let parse = |part: &str| {
+            if part.is_empty() {
+                Ok(0)
+            } else {
+                part.parse::<i128>()
+                    .map_err(|_| ParseValueError::OutOfRange)
+            }
+        };
+        let whole = parse(whole)?;
+        let fraction = parse(&format!("{fraction:0<width$}", width = usize::from(places)))?;
+
+        let magnitude = whole
+            .checked_mul(i128::from(self.unit()))
+            .and_then(|whole| whole.checked_add(fraction))
+            .ok_or(ParseValueError::OutOfRange)?;
+
+        if negative && magnitude != 0 && matches!(self, Self::Unsigned) {
+            return Err(ParseValueError::Negative);
+        }
+        let value = if negative { -magnitude } else { magnitude };
+
+        i64::try_from(value).map_err(|_| ParseValueError::OutOfRange)
+    }
+
+    /// Converts a raw value of kind `self` into the raw representation of `to`.
+    /// Reducing the number of decimal places truncates the extra digits.
+    pub fn rescale(self, raw: i64, to: Self) -> i64 {
+        let (from, to) = (self.places(), to.places());
+        if to >= from {
+            raw.saturating_mul(10_i64.pow(u32::from(to - from)))
+        } else {
+            raw / 10_i64.pow(u32::from(from - to))
+        }
+    }
+}
+
+/// Adds up values of mixed kinds. The sum is expressed in the most precise
+/// kind among them, so no digits are lost.
+pub fn total(values: impl IntoIterator<Item = (ValueKind, i64)>) -> (ValueKind, i64) {
+    let values = values.into_iter().collect::<Vec<_>>();
+    let kind = match values.iter().map(|(kind, _)| kind.places()).max() {
+        Some(places) if places > 0 => ValueKind::Decimal { places },
+        _ => ValueKind::Signed,
+    };
+    let sum = values.into_iter().fold(0_i64, |sum, (from, raw)| {
+        sum.saturating_add(from.rescale(raw, kind))
+    });
+
+    (kind, sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CENTS: ValueKind = ValueKind::Decimal { places: 2 };
+
+    #[test]
+    fn parses_whole_numbers() {
+        assert_eq!(ValueKind::Unsigned.parse("42"), Ok(42));
+        assert_eq!(ValueKind::Unsigned.parse(" +7 "), Ok(7));
+        assert_eq!(ValueKind::Signed.parse("-42"), Ok(-42));
+        assert_eq!(ValueKind::Unsigned.parse("-0"), Ok(0));
+        assert_eq!(
+            ValueKind::Unsigned.parse("-3"),
+            Err(ParseValueError::Negative)
+        );
+        assert_eq!(
+            ValueKind::Signed.parse("1.5"),
+            Err(ParseValueError::NotWhole)
+        );
+    }
+
+    #[test]
+    fn parses_decimals() {
+        assert_eq!(CENTS.parse("12.34"), Ok(1234));
+        assert_eq!(CENTS.parse("12.3"), Ok(1230));
+        assert_eq!(CENTS.parse("12"), Ok(1200));
+        assert_eq!(CENTS.parse(".5"), Ok(50));
+        assert_eq!(CENTS.parse("5."), Ok(500));
+        assert_eq!(CENTS.parse("-0.5"), Ok(-50));
+        assert_eq!(CENTS.parse("-1.25"), Ok(-125));
+        assert_eq!(ValueKind::Decimal { places: 1 }.parse("-0.5"), Ok(-5));
+        assert_eq!(
+            CENTS.parse("1.234"),
+            Err(ParseValueError::TooManyDecimals(2))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_text() {
+        for text in ["", "   "] {
+            assert_eq!(CENTS.parse(text), Err(ParseValueError::Empty), "{text:?}");
+        }
+        for text in ["abc", "-", ".", "1e3", "1.2.3", "--1", "1,5", "½"] {
+            assert_eq!(CENTS.parse(text), Err(ParseValueError::Invalid), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_values_beyond_i64() {
+        assert_eq!(ValueKind::Signed.parse("9223372036854775807"), Ok(i64::MAX));
+        assert_eq!(
+            ValueKind::Signed.parse("9223372036854775808"),
+            Err(ParseValueError::OutOfRange)
+        );
+        assert_eq!(CENTS.parse("92233720368547758.07"), Ok(i64::MAX));
+        assert_eq!(
+            CENTS.parse("92233720368547758.08"),
+            Err(ParseValueError::OutOfRange)
+        );
+        assert_eq!(
+            CENTS.parse("100000000000000000"),
+            Err(ParseValueError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn formats_fixed_point() {
+        assert_eq!(CENTS.format(1234), "12.34");
+        assert_eq!(CENTS.format(5), "0.05");
+        assert_eq!(CENTS.format(-50), "-0.50");
+        assert_eq!(CENTS.format(i64::MIN), "-92233720368547758.08");
+        assert_eq!(ValueKind::Signed.format(-3), "-3");
+        assert_eq!(CENTS.format_delta(150), "+1.50");
+        assert_eq!(CENTS.format_delta(-150), "-1.50");
+        assert_eq!(CENTS.format_delta(0), "0.00");
+    }
+
+    #[test]
+    fn formatted_values_parse_back() {
+        for raw in [0, 1, -1, 99, -50, 1234, -123_456, i64::MAX, i64::MIN] {
+            assert_eq!(CENTS.parse(&CENTS.format(raw)), Ok(raw), "{raw}");
+        }
+        let signed = ValueKind::Signed;
+        assert_eq!(signed.parse(&signed.format(i64::MIN)), Ok(i64::MIN));
+    }
+
+    #[test]
+    fn rescales_between_kinds() {
+        let places = |places| ValueKind::Decimal { places };
+        assert_eq!(ValueKind::Unsigned.rescale(5, CENTS), 500);
+        assert_eq!(CENTS.rescale(1234, places(4)), 123_400);
+        assert_eq!(places(4).rescale(123_456, CENTS), 1234);
+        assert_eq!(places(4).rescale(-123_456, CENTS), -1234);
+        assert_eq!(CENTS.rescale(-50, ValueKind::Signed), 0);
+        assert_eq!(ValueKind::Signed.rescale(i64::MAX, CENTS), i64::MAX);
+        assert_eq!(ValueKind::Signed.rescale(i64::MIN, CENTS), i64::MIN);
+    }
+
+    #[test]
+    fn totals_in_the_finest_kind() {
+        assert_eq!(
+            total([
+                (ValueKind::Unsigned, 3),
+                (CENTS, 150),
+                (ValueKind::Signed, -1)
+            ]),
+            (CENTS, 350)
+        );
+        assert_eq!(
+            total([(ValueKind::Unsigned, 3), (ValueKind::Signed, -1)]),
+            (ValueKind::Signed, 2)
+        );
+    }
+}